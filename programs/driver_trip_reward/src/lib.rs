@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
+use solana_sha256_hasher::hashv;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
+use anchor_spl::token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
 
 declare_id!("BknefWnKwdFMsMgXdgN9XWjjo55CRMrsJ2F7iQ4epURx");
 
@@ -30,8 +36,196 @@ pub enum DriverTripRewardError {
     InvalidValidatorWeight,
     #[msg("Validator not found")]
     ValidatorNotFound,
+    #[msg("Signer is not the program admin")]
+    Unauthorized,
+    #[msg("Driver is suspended")]
+    DriverSuspended,
+    #[msg("Driver is not suspended")]
+    DriverNotSuspended,
+    #[msg("Invalid suspension expiry")]
+    InvalidSuspension,
+    #[msg("Invalid trip rules")]
+    InvalidTripRules,
+    #[msg("Trip distance is below the minimum")]
+    TripDistanceTooShort,
+    #[msg("Trip distance is above the maximum")]
+    TripDistanceTooLong,
+    #[msg("Trip duration is below the minimum")]
+    TripDurationTooShort,
+    #[msg("Trip average speed is below the minimum")]
+    AverageSpeedTooLow,
+    #[msg("Trip average speed is above the maximum")]
+    AverageSpeedTooHigh,
+    #[msg("Invalid scoring config")]
+    InvalidScoringConfig,
+    #[msg("Telemetry chunk is out of order")]
+    InvalidChunkIndex,
+    #[msg("No telemetry committed for trip")]
+    TelemetryMissing,
+    #[msg("Signer is neither the driver nor a session key for the driver")]
+    InvalidSessionAuthority,
+    #[msg("Invalid session parameters")]
+    InvalidSession,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session does not allow this instruction")]
+    SessionScopeNotAllowed,
+    #[msg("Sponsor treasury is not active")]
+    SponsorTreasuryInactive,
+    #[msg("Driver's sponsored rent budget is exhausted")]
+    SponsorBudgetExceeded,
+    #[msg("Sponsor treasury has insufficient lamports")]
+    SponsorTreasuryInsufficient,
+    #[msg("Trip is still open or within its dispute window")]
+    TripNotSettled,
+    #[msg("Trip has already been rewarded")]
+    TripAlreadyRewarded,
+    #[msg("Rent payer does not match the trip")]
+    InvalidRentPayer,
+    #[msg("Trip id is out of range")]
+    InvalidTripId,
+    #[msg("Timestamp is in the future")]
+    TimestampInFuture,
+    #[msg("Timestamp is too far in the past")]
+    TimestampTooOld,
+    #[msg("Driver already has an active trip")]
+    ActiveTripExists,
+    #[msg("Trip overlaps a trip that was already counted")]
+    TripOverlap,
+    #[msg("Trip hash was already used to verify another trip")]
+    TripHashReplayed,
+    #[msg("Invalid pool claim limits")]
+    InvalidClaimLimits,
+    #[msg("Claim cooldown has not elapsed")]
+    ClaimCooldownActive,
+    #[msg("Claim exceeds the driver's daily reward cap for this pool")]
+    DailyRewardCapExceeded,
+    #[msg("Claim exceeds the driver's epoch reward cap for this pool")]
+    EpochRewardCapExceeded,
+    #[msg("Reward mint has an unsupported Token-2022 extension")]
+    UnsupportedMintExtension,
+    #[msg("Instruction does not match the reward pool's payout kind")]
+    InvalidRewardPoolKind,
+    #[msg("SOL vault cannot pay the reward and stay rent exempt")]
+    SolVaultInsufficient,
+    #[msg("Protocol fee exceeds the maximum")]
+    FeeTooHigh,
+    #[msg("Protocol treasury account is missing or does not match the config")]
+    InvalidTreasury,
+    #[msg("Reward split is invalid or its beneficiary accounts are missing")]
+    InvalidRewardSplit,
+    #[msg("Validator is not active")]
+    ValidatorInactive,
+    #[msg("Trip has already been finalized by a validator")]
+    TripAlreadyFinalized,
+    #[msg("Validator has no fees to claim")]
+    NoValidatorFees,
+    #[msg("Trip fare is escrowed and must be settled with its escrow accounts")]
+    FareEscrowRequired,
+    #[msg("Fare escrow accounts or amount do not match the trip")]
+    InvalidFareEscrow,
+    #[msg("Passenger cannot cancel the trip before its fare escrow times out")]
+    FareEscrowLocked,
+    #[msg("Passenger rating must be between 1 and 5 stars")]
+    InvalidRating,
+    #[msg("Trip has already been rated by its passenger")]
+    TripAlreadyRated,
+    #[msg("Passenger rating window has closed")]
+    RatingWindowClosed,
+    #[msg("Safety config must have penalties of at most 100 and a positive review threshold")]
+    InvalidSafetyConfig,
+    #[msg("Unsafe driving report has an unknown category or no evidence")]
+    InvalidReport,
+    #[msg("Unsafe driving report window has closed")]
+    ReportWindowClosed,
+    #[msg("Driver is under review")]
+    DriverUnderReview,
+    #[msg("Trip leg must start after the previous leg and have a positive duration and distance")]
+    InvalidLeg,
+    #[msg("Trip has too many legs")]
+    TooManyLegs,
+    #[msg("Trip distance and duration must match its legs")]
+    LegsMismatch,
+    #[msg("Checkpoint is too soon after the previous one")]
+    CheckpointTooSoon,
+    #[msg("Trip distance and duration must match its checkpoints")]
+    CheckpointMismatch,
+    #[msg("Only the sponsor treasury's relayer can have rent sponsored")]
+    SponsorRelayerRequired,
+    #[msg("Lamport recipient would be left below the rent-exempt minimum")]
+    RecipientNotRentExempt,
+    #[msg("Trip is not bound to this reward pool")]
+    TripPoolMismatch,
+    #[msg("Only trips the passenger co-signed at creation can be rated")]
+    PassengerSignatureRequired,
+    #[msg("Passenger profile is required for escrowed fares")]
+    PassengerAccountRequired,
 }
 
+// -----------------------------------------------------------------
+// Constants
+// -----------------------------------------------------------------
+pub const MAX_SESSION_DURATION: i64 = 24 * 60 * 60;
+/// How long after `end_time` a verified trip stays open for disputes.
+pub const TRIP_DISPUTE_WINDOW: i64 = 7 * 24 * 60 * 60;
+/// Clock tolerances installed by `initialize_trip_rules`.
+pub const DEFAULT_MAX_CLOCK_DRIFT: i64 = 60;
+pub const DEFAULT_MAX_TRIP_AGE: i64 = 7 * 24 * 60 * 60;
+/// How long after `end_time` the passenger may rate a trip.
+pub const PASSENGER_RATING_WINDOW: i64 = 3 * 24 * 60 * 60;
+/// Rating an unrated trip counts as in `DriverAccount.rating` until its passenger rates it.
+pub const NEUTRAL_RATING: f32 = 3.0;
+/// Minimum seconds between two `checkpoint_trip` calls on a trip.
+pub const MIN_CHECKPOINT_INTERVAL: i64 = 30;
+/// Most legs a multi-stop trip can hold, and the bytes each adds to a `TripAccount`.
+pub const MAX_TRIP_LEGS: usize = 16;
+pub const TRIP_LEG_SPACE: usize = 8 + 8 + 8 + 32 + 4;
+/// Categories of `report_unsafe_driving`, indexing `SafetyConfig.penalties`.
+pub const UNSAFE_DRIVING_SPEEDING: u8 = 0;
+pub const UNSAFE_DRIVING_PHONE_USE: u8 = 1;
+pub const UNSAFE_DRIVING_HARSH_BRAKING: u8 = 2;
+pub const UNSAFE_DRIVING_CATEGORIES: usize = 3;
+/// Ratings a new passenger is assumed to have given fairly; smooths `PassengerAccount.reliability`.
+pub const RELIABILITY_PRIOR_RATINGS: u32 = 2;
+/// Token-2022 mint extensions a reward pool accepts. Anything else (hooks,
+/// permanent delegates, confidential transfers, ...) could block or claw back
+/// transfers out of the vault.
+pub const SUPPORTED_MINT_EXTENSIONS: &[ExtensionType] = &[
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+    ExtensionType::ScaledUiAmount,
+];
+/// Upper bound on `ProgramConfig.fee_bps` (10%).
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+/// Most beneficiaries a driver's `RewardSplit` can list.
+pub const MAX_SPLIT_BENEFICIARIES: usize = 3;
+/// Length of the window tracked by `RewardPoolAccount.daily_cap_per_driver`.
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// Trips opened under a driver's `next_trip_nonce` get ids from here up, so the
+/// nonce-assigned trips are `NONCE_TRIP_ID_BASE + 0..next_trip_nonce`.
+pub const NONCE_TRIP_ID_BASE: u64 = 1 << 63;
+
+/// Instruction scopes a session key can be granted, as bits of `SessionAccount.allowed_instructions`.
+pub const SESSION_SCOPE_SUBMIT_TRIP: u32 = 1 << 0;
+pub const SESSION_SCOPE_CREATE_TRIP: u32 = 1 << 1;
+pub const SESSION_SCOPE_COMPLETE_TRIP: u32 = 1 << 2;
+pub const SESSION_SCOPE_VERIFY_TRIP: u32 = 1 << 3;
+pub const SESSION_SCOPE_COMMIT_TELEMETRY: u32 = 1 << 4;
+pub const SESSION_SCOPE_CHECKPOINT_TRIP: u32 = 1 << 5;
+pub const SESSION_SCOPE_ALL: u32 = SESSION_SCOPE_SUBMIT_TRIP
+    | SESSION_SCOPE_CREATE_TRIP
+    | SESSION_SCOPE_COMPLETE_TRIP
+    | SESSION_SCOPE_VERIFY_TRIP
+    | SESSION_SCOPE_COMMIT_TELEMETRY
+    | SESSION_SCOPE_CHECKPOINT_TRIP;
+
 // -----------------------------------------------------------------
 // State Structs (from src/state/)
 // -----------------------------------------------------------------
@@ -51,461 +245,3752 @@ pub struct DriverAccount {
     pub total_rewards: u64,
     pub validator_pubkey: Pubkey,
     pub last_trip_time: i64,
+    pub suspension_reason: u8,
+    pub suspended_until: i64,
+    pub suspension_count: u32,
+    pub sponsored_rent: u64,
+    pub rent_payer: Pubkey,
+    pub trip_history: [u8; 32],
+    pub closed_trips: u32,
+    pub next_trip_nonce: u64,
+    pub active_trip_id: u64,
+    pub active_trip_opened_at: i64,
+    pub last_counted_end_time: i64,
+    /// Claims must pass the driver's `RewardSplit` while this is set.
+    pub has_reward_split: bool,
+    pub rated_trips: u32,
+    /// Sum of passenger ratings after weighting by each passenger's reliability.
+    pub passenger_rating_total: f32,
+    pub unsafe_reports: u32,
+    pub reports_since_review: u32,
+    /// Set once `reports_since_review` reaches `SafetyConfig.review_threshold`;
+    /// reward claims are refused until an admin clears the review.
+    pub under_review: bool,
+    /// Unsafe driving penalties `total_score` was too low to cover; taken from
+    /// the driver's next scored trips.
+    pub penalty_owed: u64,
 }
 
-#[account]
-pub struct RewardPoolAccount {
-    pub pool_id: u64,
-    pub total_rewards: u64,
-    pub distributed_rewards: u64,
-    pub reward_per_trip: u64,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub is_active: bool,
-    pub bump: u8,
-    pub vault: Pubkey,
-    pub total_trip_rewards: u64,
-    pub total_driver_rewards: u64,
-    pub reward_cycle: u64,
+impl DriverAccount {
+    /// Makes `trip_id` the driver's single active trip. An active trip opened
+    /// more than `max_trip_age` ago is considered abandoned and no longer blocks.
+    pub fn begin_trip(&mut self, trip_id: u64, now: i64, max_trip_age: i64) -> Result<()> {
+        if self.active_trip_opened_at != 0 && now.saturating_sub(self.active_trip_opened_at) <= max_trip_age {
+            return Err(DriverTripRewardError::ActiveTripExists.into());
+        }
+        self.active_trip_id = trip_id;
+        self.active_trip_opened_at = now;
+        Ok(())
+    }
+
+    /// Counts a finished trip. Its range must start no earlier than the end of
+    /// the last counted trip; if it is the active trip, the slot is released.
+    pub fn finish_trip(&mut self, trip_id: u64, start_time: i64, end_time: i64) -> Result<()> {
+        if start_time < self.last_counted_end_time {
+            return Err(DriverTripRewardError::TripOverlap.into());
+        }
+        self.last_counted_end_time = end_time;
+        if self.active_trip_opened_at != 0 && self.active_trip_id == trip_id {
+            self.active_trip_id = 0;
+            self.active_trip_opened_at = 0;
+        }
+        Ok(())
+    }
+
+    /// Client-chosen ids of the legacy trip instructions must stay below
+    /// `NONCE_TRIP_ID_BASE`, so they never collide with nonce-assigned ids.
+    pub fn check_legacy_trip_id(trip_id: u64) -> Result<()> {
+        if trip_id >= NONCE_TRIP_ID_BASE {
+            return Err(DriverTripRewardError::InvalidTripId.into());
+        }
+        Ok(())
+    }
+
+    /// Id of the trip opened under `next_trip_nonce`.
+    pub fn next_trip_id(&self) -> Result<u64> {
+        NONCE_TRIP_ID_BASE.checked_add(self.next_trip_nonce).ok_or(DriverTripRewardError::InvalidTripId.into())
+    }
+
+    /// `rating` is the average passenger rating over all counted trips, with
+    /// trips their passenger has not rated counting as `NEUTRAL_RATING`.
+    pub fn refresh_rating(&mut self) {
+        if self.total_trips == 0 {
+            return;
+        }
+        let unrated = self.total_trips.saturating_sub(self.rated_trips);
+        self.rating = (self.passenger_rating_total + NEUTRAL_RATING * unrated as f32) / self.total_trips as f32;
+    }
+
+    /// Adds a scored trip to `total_score`, paying off any penalty owed first.
+    pub fn add_score(&mut self, score: u64) {
+        let settled = score.min(self.penalty_owed);
+        self.penalty_owed -= settled;
+        self.total_score += score - settled;
+        self.refresh_avg_score();
+    }
+
+    /// Takes an unsafe driving penalty from `total_score`, owing what it cannot cover.
+    pub fn apply_penalty(&mut self, penalty: u64) {
+        let taken = penalty.min(self.total_score);
+        self.total_score -= taken;
+        self.penalty_owed += penalty - taken;
+        self.refresh_avg_score();
+    }
+
+    fn refresh_avg_score(&mut self) {
+        if self.completed_trips > 0 {
+            self.avg_rating = self.total_score as f32 / self.completed_trips as f32;
+        }
+    }
+
+    /// A suspension with an expiry lifts itself once the expiry has passed.
+    pub fn is_suspended(&self, now: i64) -> bool {
+        !self.is_active && (self.suspended_until == 0 || now < self.suspended_until)
+    }
+
+    /// Next link of the closed-trip history chain:
+    /// sha256(history || trip_id || passenger || distance || duration || fare || end_time || score || trip_hash).
+    pub fn next_trip_history(history: &[u8; 32], trip: &TripAccount) -> [u8; 32] {
+        hashv(&[
+            history.as_ref(),
+            &trip.trip_id.to_le_bytes(),
+            trip.passenger_pubkey.as_ref(),
+            &trip.distance.to_le_bytes(),
+            &trip.duration.to_le_bytes(),
+            &trip.fare.to_le_bytes(),
+            &trip.end_time.to_le_bytes(),
+            &trip.score.to_le_bytes(),
+            trip.trip_hash.as_ref(),
+        ])
+        .to_bytes()
+    }
 }
 
 #[account]
-pub struct TripAccount {
-    pub trip_id: u64,
-    pub driver_pubkey: Pubkey,
-    pub passenger_pubkey: Pubkey,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub distance: u64,
-    pub duration: u64,
-    pub fare: u64,
-    pub rating: f32,
-    pub status: u8,
+pub struct ProgramConfig {
+    pub admin_pubkey: Pubkey,
     pub bump: u8,
-    pub score: u32,
-    pub trip_hash: [u8; 32],
-    pub verification_status: u8,
-    pub validator_pubkey: Pubkey,
+    /// Protocol fee taken from each claim, in basis points.
+    pub fee_bps: u16,
+    /// Receives protocol fees: lamports directly for SOL pools, and its
+    /// associated token account for the reward mint for token pools.
+    pub treasury: Pubkey,
+}
+
+impl ProgramConfig {
+    pub fn protocol_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
 }
 
+/// Program-owned lamport treasury that reimburses rent for driver and trip
+/// accounts. `per_driver_budget` caps the sponsored rent a driver may have
+/// outstanding at once; rent refunded on close frees the budget again. Only
+/// inits whose fee payer is `relayer` are reimbursed, so the relayer decides
+/// which drivers and trips the treasury pays for.
 #[account]
-pub struct ValidatorAccount {
-    pub validator_pubkey: Pubkey,
-    pub public_key: String,
-    pub private_key: String,
+pub struct SponsorTreasury {
+    pub per_driver_budget: u64,
+    pub relayer: Pubkey,
+    pub total_sponsored: u64,
+    pub total_refunded: u64,
     pub is_active: bool,
     pub bump: u8,
-    pub total_validations: u64,
-    pub success_rate: f32,
-    pub last_validation_time: i64,
-    pub validator_weight: f32,
 }
 
-// -----------------------------------------------------------------
-// Program Logic (#[program])
-// -----------------------------------------------------------------
-#[program]
-pub mod driver_trip_reward {
-    use super::*;
+/// Plausibility bounds for completed trips. Distances use the unit of
+/// `TripAccount.distance`, durations are seconds and speeds are distance
+/// units per hour. Client timestamps may run at most `max_clock_drift`
+/// seconds ahead of the cluster clock and `max_trip_age` seconds behind it.
+#[account]
+pub struct TripRules {
+    pub min_distance: u64,
+    pub max_distance: u64,
+    pub min_duration: u64,
+    pub min_avg_speed: u64,
+    pub max_avg_speed: u64,
+    pub bump: u8,
+    pub max_clock_drift: i64,
+    pub max_trip_age: i64,
+}
 
-    /// Initialize a new driver account
-    pub fn initialize_driver(ctx: Context<InitializeDriver>) -> Result<()> {
-        if ctx.accounts.driver_account.driver_pubkey != Pubkey::default() {
-            return Err(DriverTripRewardError::DriverAlreadyExists.into());
+impl TripRules {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_distance == 0 || self.min_distance > self.max_distance {
+            return Err(DriverTripRewardError::InvalidTripRules.into());
+        }
+        if self.max_avg_speed == 0 || self.min_avg_speed > self.max_avg_speed {
+            return Err(DriverTripRewardError::InvalidTripRules.into());
+        }
+        if self.max_clock_drift < 0 || self.max_trip_age <= 0 {
+            return Err(DriverTripRewardError::InvalidTripRules.into());
         }
-        ctx.accounts.driver_account.driver_pubkey = ctx.accounts.driver_pubkey.key();
-        ctx.accounts.driver_account.total_trips = 0;
-        ctx.accounts.driver_account.total_earnings = 0;
-        ctx.accounts.driver_account.total_distance = 0;
-        ctx.accounts.driver_account.total_time = 0;
-        ctx.accounts.driver_account.rating = 0.0;
-        ctx.accounts.driver_account.is_active = true;
-        ctx.accounts.driver_account.bump = ctx.bumps.driver_account;
-        ctx.accounts.driver_account.total_score = 0;
-        ctx.accounts.driver_account.completed_trips = 0;
-        ctx.accounts.driver_account.avg_rating = 0.0;
-        ctx.accounts.driver_account.total_rewards = 0;
-        ctx.accounts.driver_account.validator_pubkey = Pubkey::default();
-        ctx.accounts.driver_account.last_trip_time = 0;
         Ok(())
     }
 
-    /// Submit a new trip for verification
-    pub fn submit_trip(ctx: Context<SubmitTrip>, passenger_pubkey: Pubkey, trip_id: u64, start_time: i64, distance: u64, duration: u64, fare: u64) -> Result<()> {
-        if start_time <= 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
-        }
-        if distance == 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
-        }
-        if duration == 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+    pub fn check_timestamp(&self, timestamp: i64, now: i64) -> Result<()> {
+        if timestamp > now.saturating_add(self.max_clock_drift) {
+            return Err(DriverTripRewardError::TimestampInFuture.into());
         }
-        if fare == 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        if timestamp < now.saturating_sub(self.max_trip_age) {
+            return Err(DriverTripRewardError::TimestampTooOld.into());
         }
-        ctx.accounts.trip_account.trip_id = trip_id;
-        ctx.accounts.trip_account.driver_pubkey = ctx.accounts.driver_pubkey.key();
-        ctx.accounts.trip_account.passenger_pubkey = passenger_pubkey;
-        ctx.accounts.trip_account.start_time = start_time;
-        ctx.accounts.trip_account.end_time = 0;
-        ctx.accounts.trip_account.distance = distance;
-        ctx.accounts.trip_account.duration = duration;
-        ctx.accounts.trip_account.fare = fare;
-        ctx.accounts.trip_account.rating = 0.0;
-        ctx.accounts.trip_account.status = 0;
-        ctx.accounts.trip_account.score = 0;
-        ctx.accounts.trip_account.trip_hash = [0u8; 32];
-        ctx.accounts.trip_account.verification_status = 0;
-        ctx.accounts.trip_account.validator_pubkey = Pubkey::default();
-        ctx.accounts.trip_account.bump = ctx.bumps.trip_account;
         Ok(())
     }
 
-    /// Verify a trip and calculate trip score
-    pub fn verify_trip(ctx: Context<VerifyTrip>, trip_id: u64, end_time: i64, rating: f32, trip_hash: [u8; 32]) -> Result<()> {
-        if ctx.accounts.trip_account.trip_id != trip_id {
-            return Err(DriverTripRewardError::TripNotFound.into());
+    pub fn check_trip(&self, distance: u64, duration: u64) -> Result<()> {
+        if distance < self.min_distance {
+            return Err(DriverTripRewardError::TripDistanceTooShort.into());
         }
-        if ctx.accounts.trip_account.status != 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        if distance > self.max_distance {
+            return Err(DriverTripRewardError::TripDistanceTooLong.into());
         }
-        if end_time <= ctx.accounts.trip_account.start_time {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        if duration < self.min_duration {
+            return Err(DriverTripRewardError::TripDurationTooShort.into());
         }
-        if rating < 0.0 || rating > 5.0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        // Compare distance * 3600 / duration against the bounds without dividing.
+        let travelled = distance as u128 * 3600;
+        if travelled < self.min_avg_speed as u128 * duration as u128 {
+            return Err(DriverTripRewardError::AverageSpeedTooLow.into());
+        }
+        if travelled > self.max_avg_speed as u128 * duration as u128 {
+            return Err(DriverTripRewardError::AverageSpeedTooHigh.into());
+        }
+        Ok(())
+    }
+
+    /// Speed check for the stretch between two checkpoints. Only the upper bound
+    /// applies, since a short stretch may be spent standing in traffic.
+    pub fn check_segment(&self, distance: u64, duration: u64) -> Result<()> {
+        if distance as u128 * 3600 > self.max_avg_speed as u128 * duration as u128 {
+            return Err(DriverTripRewardError::AverageSpeedTooHigh.into());
         }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ScoringWeights {
+    pub rating_weight: u32,
+    pub distance_threshold: u64,
+    pub distance_bonus: u32,
+    pub min_bonus_duration: u64,
+    pub max_bonus_duration: u64,
+    pub duration_bonus: u32,
+    pub fare_threshold: u64,
+    pub fare_bonus: u32,
+    pub max_score: u32,
+}
 
-        let mut score = 0u32;
-        score += (rating * 20.0) as u32;
-        if ctx.accounts.trip_account.distance > 1 {
-            score += 50;
+impl ScoringWeights {
+    pub const SIZE: usize = 4 + 8 + 4 + 8 + 8 + 4 + 8 + 4 + 4;
+
+    pub fn validate(&self) -> Result<()> {
+        if self.max_score == 0 || self.min_bonus_duration > self.max_bonus_duration {
+            return Err(DriverTripRewardError::InvalidScoringConfig.into());
         }
-        if ctx.accounts.trip_account.duration > 60 && ctx.accounts.trip_account.duration < 3600 {
-            score += 30;
+        Ok(())
+    }
+
+    pub fn score(&self, rating: f32, distance: u64, duration: u64, fare: u64) -> u32 {
+        let mut score = (rating * self.rating_weight as f32) as u32;
+        if distance > self.distance_threshold {
+            score = score.saturating_add(self.distance_bonus);
         }
-        if ctx.accounts.trip_account.fare > 100000000 {
-            score += 20;
+        if duration > self.min_bonus_duration && duration < self.max_bonus_duration {
+            score = score.saturating_add(self.duration_bonus);
         }
-        if score > 100 {
-            score = 100;
+        if fare > self.fare_threshold {
+            score = score.saturating_add(self.fare_bonus);
         }
+        score.min(self.max_score)
+    }
+}
 
-        ctx.accounts.trip_account.end_time = end_time;
-        ctx.accounts.trip_account.rating = rating;
-        ctx.accounts.trip_account.score = score;
-        ctx.accounts.trip_account.trip_hash = trip_hash;
-        ctx.accounts.trip_account.verification_status = 1;
-        ctx.accounts.trip_account.status = 1;
-        ctx.accounts.trip_account.validator_pubkey = Pubkey::default();
-
-        ctx.accounts.driver_account.total_trips += 1;
-        ctx.accounts.driver_account.completed_trips += 1;
-        ctx.accounts.driver_account.total_earnings += ctx.accounts.trip_account.fare;
-        ctx.accounts.driver_account.total_distance += ctx.accounts.trip_account.distance;
-        ctx.accounts.driver_account.total_time += ctx.accounts.trip_account.duration;
-        ctx.accounts.driver_account.total_score += score as u64;
+#[account]
+pub struct ScoringConfig {
+    pub version: u32,
+    pub weights: ScoringWeights,
+    pub published_at: i64,
+    pub bump: u8,
+}
 
-        let old_rating = ctx.accounts.driver_account.rating;
-        let old_trips = ctx.accounts.driver_account.total_trips - 1;
+/// The weights of one published scoring version, seeded by the version so a
+/// trip's `scoring_version` always resolves to the weights it was scored with.
+#[account]
+pub struct ScoringVersion {
+    pub version: u32,
+    pub weights: ScoringWeights,
+    pub published_at: i64,
+    pub bump: u8,
+}
 
-        if old_trips > 0 {
-            let new_rating = (old_rating * (old_trips as f32) + rating) / (old_trips as f32 + 1.0);
-            ctx.accounts.driver_account.rating = new_rating;
-        } else {
-            ctx.accounts.driver_account.rating = rating;
-        }
+#[account]
+pub struct SessionAccount {
+    pub driver_pubkey: Pubkey,
+    pub session_key: Pubkey,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub allowed_instructions: u32,
+    pub bump: u8,
+}
 
-        if ctx.accounts.driver_account.completed_trips > 0 {
-            let avg_score = (ctx.accounts.driver_account.total_score as f32) / (ctx.accounts.driver_account.completed_trips as f32);
-            ctx.accounts.driver_account.avg_rating = avg_score;
-        }
+#[account]
+pub struct SuspensionRecord {
+    pub driver_pubkey: Pubkey,
+    pub index: u32,
+    pub reason_code: u8,
+    pub suspended_at: i64,
+    pub expires_at: i64,
+    pub suspended_by: Pubkey,
+    pub reactivated_at: i64,
+    pub reactivated_by: Pubkey,
+    pub bump: u8,
+}
 
-        ctx.accounts.driver_account.last_trip_time = end_time;
-        Ok(())
-    }
+#[account]
+pub struct RewardPoolAccount {
+    pub pool_id: u64,
+    pub total_rewards: u64,
+    pub distributed_rewards: u64,
+    pub reward_per_trip: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub is_active: bool,
+    pub bump: u8,
+    pub vault: Pubkey,
+    pub total_trip_rewards: u64,
+    pub total_driver_rewards: u64,
+    pub reward_cycle: u64,
+    /// Per-driver claim limits; zero disables the limit.
+    pub daily_cap_per_driver: u64,
+    pub epoch_cap_per_driver: u64,
+    pub claim_cooldown: i64,
+    /// Transfer fees withheld from claims; `distributed_rewards` counts what left
+    /// the vault, so drivers received `distributed_rewards - total_transfer_fees`.
+    pub total_transfer_fees: u64,
+    /// Pays lamports from a `SolVault` instead of tokens from a token vault.
+    pub is_native: bool,
+    pub total_protocol_fees: u64,
+    /// Paid to the validator that finalizes a trip, reserved out of
+    /// `total_rewards` when it accrues.
+    pub validator_fee: u64,
+    pub total_validator_fees: u64,
+}
 
-    /// Initialize a new reward pool
-    pub fn initialize_reward_pool(ctx: Context<InitializeRewardPool>, pool_id: u64, total_rewards: u64, reward_per_trip: u64, start_time: i64, end_time: i64) -> Result<()> {
+impl RewardPoolAccount {
+    /// Validates the pool parameters and resets a freshly initialized pool.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(&mut self, pool_id: u64, total_rewards: u64, reward_per_trip: u64, start_time: i64, end_time: i64, vault: Pubkey, is_native: bool, bump: u8) -> Result<()> {
         if start_time >= end_time {
             return Err(DriverTripRewardError::RewardPoolNotFound.into());
         }
         if total_rewards == 0 {
             return Err(DriverTripRewardError::RewardPoolNotFound.into());
         }
-        ctx.accounts.reward_pool_account.pool_id = pool_id;
-        ctx.accounts.reward_pool_account.total_rewards = total_rewards;
-        ctx.accounts.reward_pool_account.distributed_rewards = 0;
-        ctx.accounts.reward_pool_account.reward_per_trip = reward_per_trip;
-        ctx.accounts.reward_pool_account.start_time = start_time;
-        ctx.accounts.reward_pool_account.end_time = end_time;
-        ctx.accounts.reward_pool_account.is_active = true;
-        ctx.accounts.reward_pool_account.bump = ctx.bumps.reward_pool_account;
-        ctx.accounts.reward_pool_account.vault = ctx.accounts.vault.key();
-        ctx.accounts.reward_pool_account.total_trip_rewards = 0;
-        ctx.accounts.reward_pool_account.total_driver_rewards = 0;
-        ctx.accounts.reward_pool_account.reward_cycle = 0;
-
-        msg!("✅ Reward pool initialized with vault: {}", ctx.accounts.vault.key());
+        self.pool_id = pool_id;
+        self.total_rewards = total_rewards;
+        self.distributed_rewards = 0;
+        self.reward_per_trip = reward_per_trip;
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.is_active = true;
+        self.bump = bump;
+        self.vault = vault;
+        self.total_trip_rewards = 0;
+        self.total_driver_rewards = 0;
+        self.reward_cycle = 0;
+        self.daily_cap_per_driver = 0;
+        self.epoch_cap_per_driver = 0;
+        self.claim_cooldown = 0;
+        self.total_transfer_fees = 0;
+        self.is_native = is_native;
+        self.total_protocol_fees = 0;
+        self.validator_fee = 0;
+        self.total_validator_fees = 0;
         Ok(())
     }
 
-    /// Initialize a new validator account
-    pub fn initialize_validator(ctx: Context<InitializeValidator>, public_key: String, private_key: String) -> Result<()> {
-        if ctx.accounts.validator_account.validator_pubkey != Pubkey::default() {
-            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+    /// Checks that `driver_account` may claim from this pool, optionally for
+    /// `trip_account`, and returns the reward amount. The trip receipt is
+    /// advisory: it only keeps a trip from being passed with two claims, and
+    /// claims without one are bounded by the pool's cooldown and caps alone.
+    pub fn check_claim(&self, pool_id: u64, reward_pool: Pubkey, driver_account: &DriverAccount, trip_account: Option<&TripAccount>, now: i64) -> Result<u64> {
+        if driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
         }
-        if public_key.len() < 10 || public_key.len() > 1000 {
-            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+        if driver_account.under_review {
+            return Err(DriverTripRewardError::DriverUnderReview.into());
         }
-        if private_key.len() < 10 || private_key.len() > 1000 {
-            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+        if self.pool_id != pool_id {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
         }
-        ctx.accounts.validator_account.validator_pubkey = ctx.accounts.validator_pubkey.key();
-        ctx.accounts.validator_account.public_key = public_key;
-        ctx.accounts.validator_account.private_key = private_key;
-        ctx.accounts.validator_account.is_active = true;
-        ctx.accounts.validator_account.bump = ctx.bumps.validator_account;
-        ctx.accounts.validator_account.total_validations = 0;
-        ctx.accounts.validator_account.success_rate = 0.0;
-        ctx.accounts.validator_account.last_validation_time = 0;
-        ctx.accounts.validator_account.validator_weight = 1.0;
-        Ok(())
-    }
-
-    /// Process private data
-    pub fn process_private_data(ctx: Context<ProcessPrivateData>, data: String, operation: String) -> Result<()> {
-        if operation != "encrypt" && operation != "decrypt" && operation != "hash" {
-            return Err(DriverTripRewardError::InvalidDataOperation.into());
+        if !self.is_active {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
         }
-        if data.len() > 10000 {
-            return Err(DriverTripRewardError::InvalidDataOperation.into());
+        if self.distributed_rewards >= self.total_rewards {
+            return Err(DriverTripRewardError::InsufficientRewards.into());
         }
-        ctx.accounts.validator_account.total_validations += 1;
-        ctx.accounts.validator_account.last_validation_time = Clock::get().unwrap().unix_timestamp;
-
-        msg!("Processing private data with operation: {}", operation);
-        msg!("Data length: {}", data.len());
-        Ok(())
-    }
-
-    /// Claim rewards for completed trips
-    pub fn claim_rewards(ctx: Context<ClaimRewards>, pool_id: u64) -> Result<()> {
-        if ctx.accounts.reward_pool_account.pool_id != pool_id {
+        if driver_account.completed_trips == 0 {
             return Err(DriverTripRewardError::RewardPoolNotFound.into());
         }
-        if !ctx.accounts.reward_pool_account.is_active {
-            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        if let Some(trip_account) = trip_account {
+            if trip_account.verification_status != 1 {
+                return Err(DriverTripRewardError::InvalidTripStatus.into());
+            }
+            if trip_account.rewarded_at != 0 {
+                return Err(DriverTripRewardError::TripAlreadyRewarded.into());
+            }
+            if trip_account.reward_pool != Pubkey::default() && trip_account.reward_pool != reward_pool {
+                return Err(DriverTripRewardError::TripPoolMismatch.into());
+            }
         }
-        if ctx.accounts.reward_pool_account.distributed_rewards >= ctx.accounts.reward_pool_account.total_rewards {
+        let reward_amount = self.reward_per_trip;
+        if self.distributed_rewards.saturating_add(reward_amount) > self.total_rewards {
             return Err(DriverTripRewardError::InsufficientRewards.into());
         }
-        if ctx.accounts.driver_account.completed_trips == 0 {
-            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        Ok(reward_amount)
+    }
+
+    /// Records a paid claim.
+    pub fn record_payout(&mut self, driver_account: &mut DriverAccount, trip_account: Option<&mut TripAccount>, payout: &Payout, now: i64) {
+        let driver_share = payout.reward_amount.saturating_sub(payout.protocol_fee).saturating_sub(payout.donated);
+        self.distributed_rewards = self.distributed_rewards.saturating_add(payout.reward_amount);
+        self.total_protocol_fees = self.total_protocol_fees.saturating_add(payout.protocol_fee);
+        self.total_transfer_fees = self.total_transfer_fees.saturating_add(driver_share.saturating_sub(payout.received));
+        self.total_trip_rewards = self.total_trip_rewards.saturating_add(payout.received);
+        driver_account.total_rewards = driver_account.total_rewards.saturating_add(payout.received);
+        if let Some(trip_account) = trip_account {
+            trip_account.rewarded_at = now;
         }
+    }
+}
 
-        let reward_amount = ctx.accounts.reward_pool_account.reward_per_trip;
+/// How a claim's `reward_amount` left the vault: `protocol_fee` to the
+/// treasury, `donated` to the driver's split beneficiaries, and the rest to the
+/// driver, who `received` it less any transfer fees.
+pub struct Payout {
+    pub reward_amount: u64,
+    pub protocol_fee: u64,
+    pub donated: u64,
+    pub received: u64,
+}
 
-        if ctx.accounts
-			.reward_pool_account
-			.distributed_rewards
-			.saturating_add(reward_amount)
-			> ctx.accounts.reward_pool_account.total_rewards
-		{
-			return Err(DriverTripRewardError::InsufficientRewards.into());
-		}
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SplitShare {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
 
-        let seeds = &[
-            b"vault_authority",
-            ctx.accounts.reward_pool_account.to_account_info().key.as_ref(),
-            &[ctx.bumps.vault_authority],
-        ];
-		let signer_seeds: &[&[&[u8]]] = &[seeds];
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SplitBeneficiary {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+    pub total_donated: u64,
+}
 
-		// decimals needed for transfer_checked
-		let decimals = ctx.accounts.reward_mint.decimals;
+impl SplitBeneficiary {
+    pub fn share_of(&self, amount: u64) -> u64 {
+        (amount as u128 * self.share_bps as u128 / 10_000) as u64
+    }
+}
 
-		// must use TransferChecked and include mint
-		let cpi_accounts = TransferChecked {
-			from: ctx.accounts.vault.to_account_info(),
-			mint: ctx.accounts.reward_mint.to_account_info(),
-			to: ctx.accounts.driver_token_account.to_account_info(),
-			authority: ctx.accounts.vault_authority.to_account_info(),
-		};
-		let cpi_program = ctx.accounts.token_program.to_account_info();
-		let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-		transfer_checked(cpi_ctx, reward_amount, decimals)?;
-
-		ctx.accounts.reward_pool_account.distributed_rewards =
-			ctx.accounts.reward_pool_account.distributed_rewards.saturating_add(reward_amount);
-		ctx.accounts.reward_pool_account.total_trip_rewards =
-			ctx.accounts.reward_pool_account.total_trip_rewards.saturating_add(reward_amount);
-		ctx.accounts.driver_account.total_rewards =
-			ctx.accounts.driver_account.total_rewards.saturating_add(reward_amount);
+/// Part of a driver's rewards donated to beneficiaries on every claim. Token
+/// claims pay each beneficiary's associated token account for the reward mint;
+/// SOL claims pay the wallet.
+#[account]
+pub struct RewardSplit {
+    pub driver_pubkey: Pubkey,
+    pub beneficiaries: Vec<SplitBeneficiary>,
+    pub total_donated: u64,
+    pub bump: u8,
+}
 
-		msg!("✅ Transferred {} tokens to driver {}", reward_amount, ctx.accounts.driver_pubkey.key());
-		Ok(())
+impl RewardSplit {
+    /// Replaces the beneficiaries, keeping the running totals of wallets that
+    /// stay in the split.
+    pub fn set_shares(&mut self, shares: &[SplitShare]) -> Result<()> {
+        if shares.len() > MAX_SPLIT_BENEFICIARIES {
+            return Err(DriverTripRewardError::InvalidRewardSplit.into());
+        }
+        let mut total_bps: u32 = 0;
+        for (index, share) in shares.iter().enumerate() {
+            if share.share_bps == 0 || share.wallet == Pubkey::default() {
+                return Err(DriverTripRewardError::InvalidRewardSplit.into());
+            }
+            if shares[..index].iter().any(|other| other.wallet == share.wallet) {
+                return Err(DriverTripRewardError::InvalidRewardSplit.into());
+            }
+            total_bps += share.share_bps as u32;
+        }
+        if total_bps > 10_000 {
+            return Err(DriverTripRewardError::InvalidRewardSplit.into());
+        }
+        self.beneficiaries = shares
+            .iter()
+            .map(|share| SplitBeneficiary {
+                wallet: share.wallet,
+                share_bps: share.share_bps,
+                total_donated: self
+                    .beneficiaries
+                    .iter()
+                    .find(|beneficiary| beneficiary.wallet == share.wallet)
+                    .map_or(0, |beneficiary| beneficiary.total_donated),
+            })
+            .collect();
+        Ok(())
     }
 
-    /// Update validator weights
-    pub fn update_validator_weights(ctx: Context<UpdateValidatorWeights>, new_weights: Vec<u8>) -> Result<()> {
-        if new_weights.len() == 0 || new_weights.len() > 10000 {
-            return Err(DriverTripRewardError::InvalidDataOperation.into());
+    /// Checks that `accounts` are the beneficiaries' payout accounts, in order,
+    /// where `payout_account` maps a wallet to the account it is paid through.
+    pub fn check_accounts(&self, accounts: &[AccountInfo], payout_account: impl Fn(&Pubkey) -> Pubkey) -> Result<()> {
+        if accounts.len() != self.beneficiaries.len() {
+            return Err(DriverTripRewardError::InvalidRewardSplit.into());
+        }
+        for (beneficiary, account) in self.beneficiaries.iter().zip(accounts) {
+            if account.key() != payout_account(&beneficiary.wallet) || !account.is_writable {
+                return Err(DriverTripRewardError::InvalidRewardSplit.into());
+            }
         }
+        Ok(())
+    }
+}
 
-        msg!("Validator weights updated with {} bytes", new_weights.len());
+/// Lamport vault of a native SOL reward pool.
+#[account]
+pub struct SolVault {
+    pub reward_pool: Pubkey,
+    pub bump: u8,
+}
 
-        ctx.accounts.validator_account.total_validations += 1;
-        ctx.accounts.validator_account.last_validation_time = Clock::get().unwrap().unix_timestamp;
-        Ok(())
+/// Claims made by one driver against one pool, for enforcing the pool's limits.
+#[account]
+pub struct DriverPoolState {
+    pub reward_pool: Pubkey,
+    pub driver_pubkey: Pubkey,
+    pub day: i64,
+    pub claimed_today: u64,
+    pub epoch: u64,
+    pub claimed_this_epoch: u64,
+    pub last_claim_at: i64,
+    /// Gross amount claimed, and how much of it went to protocol fees and
+    /// reached the driver.
+    pub total_claimed: u64,
+    pub bump: u8,
+    pub total_protocol_fees: u64,
+    pub total_received: u64,
+}
+
+impl DriverPoolState {
+    /// Binds a freshly created state account to its pool and driver.
+    pub fn bind(&mut self, reward_pool: Pubkey, driver_pubkey: Pubkey, bump: u8) {
+        if self.driver_pubkey == Pubkey::default() {
+            self.reward_pool = reward_pool;
+            self.driver_pubkey = driver_pubkey;
+            self.bump = bump;
+        }
     }
 
-    /// Complete a trip and update driver statistics
-    pub fn complete_trip(ctx: Context<CompleteTrip>, trip_id: u64, end_time: i64, distance: u64, duration: u64, fare: u64, rating: f32) -> Result<()> {
-        if ctx.accounts.trip_account.trip_id != trip_id {
-            return Err(DriverTripRewardError::TripNotFound.into());
+    pub fn record_payout(&mut self, payout: &Payout) {
+        self.total_protocol_fees = self.total_protocol_fees.saturating_add(payout.protocol_fee);
+        self.total_received = self.total_received.saturating_add(payout.received);
+    }
+
+    /// Rolls the day and epoch windows forward and records a claim of `amount`
+    /// if it respects the pool's cooldown and caps.
+    pub fn record_claim(&mut self, pool: &RewardPoolAccount, amount: u64, now: i64, epoch: u64) -> Result<()> {
+        if self.last_claim_at != 0 && now < self.last_claim_at.saturating_add(pool.claim_cooldown) {
+            msg!("Next claim allowed at {}", self.last_claim_at.saturating_add(pool.claim_cooldown));
+            return Err(DriverTripRewardError::ClaimCooldownActive.into());
         }
-        if ctx.accounts.trip_account.status != 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        let day = now.div_euclid(SECONDS_PER_DAY);
+        if self.day != day {
+            self.day = day;
+            self.claimed_today = 0;
         }
-        if end_time <= ctx.accounts.trip_account.start_time {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        if self.epoch != epoch {
+            self.epoch = epoch;
+            self.claimed_this_epoch = 0;
         }
-        if rating < 0.0 || rating > 5.0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        if pool.daily_cap_per_driver != 0 {
+            let remaining = pool.daily_cap_per_driver.saturating_sub(self.claimed_today);
+            if amount > remaining {
+                msg!("Daily reward cap exceeded: {} remaining of {}", remaining, pool.daily_cap_per_driver);
+                return Err(DriverTripRewardError::DailyRewardCapExceeded.into());
+            }
         }
+        if pool.epoch_cap_per_driver != 0 {
+            let remaining = pool.epoch_cap_per_driver.saturating_sub(self.claimed_this_epoch);
+            if amount > remaining {
+                msg!("Epoch reward cap exceeded: {} remaining of {}", remaining, pool.epoch_cap_per_driver);
+                return Err(DriverTripRewardError::EpochRewardCapExceeded.into());
+            }
+        }
+        self.claimed_today = self.claimed_today.saturating_add(amount);
+        self.claimed_this_epoch = self.claimed_this_epoch.saturating_add(amount);
+        self.total_claimed = self.total_claimed.saturating_add(amount);
+        self.last_claim_at = now;
+        Ok(())
+    }
+}
 
-        ctx.accounts.trip_account.end_time = end_time;
-        ctx.accounts.trip_account.distance = distance;
-        ctx.accounts.trip_account.duration = duration;
-        ctx.accounts.trip_account.fare = fare;
-        ctx.accounts.trip_account.rating = rating;
-        ctx.accounts.trip_account.status = 1;
-
-        ctx.accounts.driver_account.total_trips += 1;
-        ctx.accounts.driver_account.total_earnings += fare;
-        ctx.accounts.driver_account.total_distance += distance;
-        ctx.accounts.driver_account.total_time += duration;
+#[account]
+pub struct TripAccount {
+    pub trip_id: u64,
+    pub driver_pubkey: Pubkey,
+    pub passenger_pubkey: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub distance: u64,
+    pub duration: u64,
+    pub fare: u64,
+    pub rating: f32,
+    pub status: u8,
+    pub bump: u8,
+    pub score: u32,
+    pub trip_hash: [u8; 32],
+    pub verification_status: u8,
+    pub validator_pubkey: Pubkey,
+    pub scoring_version: u32,
+    pub telemetry_chain: [u8; 32],
+    pub telemetry_chunks: u32,
+    pub rent_payer: Pubkey,
+    pub rewarded_at: i64,
+    /// Pool the trip was bound to when verified; only it pays the validator fee
+    /// for finalizing the trip or accepts the trip as a claim receipt.
+    pub reward_pool: Pubkey,
+    /// The fare is held in this trip's `FareEscrow` until completion or cancellation.
+    pub has_fare_escrow: bool,
+    /// The passenger co-signed the trip's creation, so only they can rate it.
+    pub passenger_signed: bool,
+    /// Stars (1-5) from the passenger's `rate_trip`; 0 while unrated.
+    pub passenger_rating: u8,
+    pub rated_at: i64,
+    /// Distance and duration accumulated by `checkpoint_trip` while the trip is open.
+    pub checkpoints: u32,
+    pub checkpoint_distance: u64,
+    pub checkpoint_duration: u64,
+    pub last_checkpoint_at: i64,
+    /// Ordered stops of a multi-stop trip. The account grows by `TRIP_LEG_SPACE`
+    /// per leg, so this must stay the last field.
+    pub legs: Vec<TripLeg>,
+}
 
-        let old_rating = ctx.accounts.driver_account.rating;
-        let old_trips = ctx.accounts.driver_account.total_trips - 1;
+/// One stop-to-stop leg of a trip. `passenger` is `Pubkey::default()` when the
+/// leg carried none.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TripLeg {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub distance: u64,
+    pub passenger: Pubkey,
+    pub score: u32,
+}
 
-        if old_trips > 0 {
-            let new_rating = (old_rating * (old_trips as f32) + rating) / (old_trips as f32 + 1.0);
-            ctx.accounts.driver_account.rating = new_rating;
-        } else {
-            ctx.accounts.driver_account.rating = rating;
+impl TripLeg {
+    /// Driving time of the leg; a leg ending before it starts is invalid.
+    pub fn duration(&self) -> Result<u64> {
+        let duration = self.end_time.checked_sub(self.start_time).ok_or(DriverTripRewardError::InvalidLeg)?;
+        if duration < 0 {
+            return Err(DriverTripRewardError::InvalidLeg.into());
         }
+        Ok(duration as u64)
+    }
+}
 
-        ctx.accounts.driver_account.last_trip_time = end_time;
-        Ok(())
+impl TripAccount {
+    /// Resets a freshly initialized trip to the pending state.
+    pub fn open(&mut self, trip_id: u64, driver_pubkey: Pubkey, passenger_pubkey: Pubkey, start_time: i64, rent_payer: Pubkey, bump: u8) {
+        self.trip_id = trip_id;
+        self.driver_pubkey = driver_pubkey;
+        self.passenger_pubkey = passenger_pubkey;
+        self.start_time = start_time;
+        self.end_time = 0;
+        self.distance = 0;
+        self.duration = 0;
+        self.fare = 0;
+        self.rating = 0.0;
+        self.status = 0;
+        self.score = 0;
+        self.trip_hash = [0u8; 32];
+        self.verification_status = 0;
+        self.validator_pubkey = Pubkey::default();
+        self.scoring_version = 0;
+        self.telemetry_chain = [0u8; 32];
+        self.telemetry_chunks = 0;
+        self.rent_payer = rent_payer;
+        self.rewarded_at = 0;
+        self.reward_pool = Pubkey::default();
+        self.has_fare_escrow = false;
+        self.passenger_signed = false;
+        self.passenger_rating = 0;
+        self.rated_at = 0;
+        self.checkpoints = 0;
+        self.checkpoint_distance = 0;
+        self.checkpoint_duration = 0;
+        self.last_checkpoint_at = start_time;
+        self.legs = Vec::new();
+        self.bump = bump;
     }
 
-    /// Create a new trip account
-    pub fn create_trip(ctx: Context<CreateTrip>, passenger_pubkey: Pubkey, trip_id: u64, start_time: i64) -> Result<()> {
-        if ctx.accounts.trip_account.trip_id != 0 {
-            return Err(DriverTripRewardError::TripNotFound.into());
+    /// Total distance and driving time (summed leg durations) over the legs.
+    pub fn leg_totals(&self) -> Result<(u64, u64)> {
+        self.legs.iter().try_fold((0u64, 0u64), |(distance, duration), leg| {
+            let distance = distance.checked_add(leg.distance).ok_or(DriverTripRewardError::InvalidLeg)?;
+            let duration = duration.checked_add(leg.duration()?).ok_or(DriverTripRewardError::InvalidLeg)?;
+            Ok((distance, duration))
+        })
+    }
+
+    /// The distance and duration a trip is completed or verified with: the
+    /// legs' totals when it has legs, which the client's values must match.
+    pub fn totals_from_legs(&self, distance: u64, duration: u64) -> Result<(u64, u64)> {
+        if self.legs.is_empty() {
+            return Ok((distance, duration));
         }
-        if start_time <= 0 {
-            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        let totals = self.leg_totals()?;
+        if totals != (distance, duration) {
+            return Err(DriverTripRewardError::LegsMismatch.into());
         }
+        Ok(totals)
+    }
 
-        ctx.accounts.trip_account.trip_id = trip_id;
-        ctx.accounts.trip_account.driver_pubkey = ctx.accounts.driver_pubkey.key();
-        ctx.accounts.trip_account.passenger_pubkey = passenger_pubkey;
-        ctx.accounts.trip_account.start_time = start_time;
-        ctx.accounts.trip_account.end_time = 0;
-        ctx.accounts.trip_account.distance = 0;
-        ctx.accounts.trip_account.duration = 0;
-        ctx.accounts.trip_account.fare = 0;
-        ctx.accounts.trip_account.rating = 0.0;
-        ctx.accounts.trip_account.status = 0;
-        ctx.accounts.trip_account.score = 0;
-        ctx.accounts.trip_account.trip_hash = [0u8; 32];
-        ctx.accounts.trip_account.verification_status = 0;
-        ctx.accounts.trip_account.validator_pubkey = Pubkey::default();
-        ctx.accounts.trip_account.bump = ctx.bumps.trip_account;
+    /// A checkpointed trip must be completed with the checkpoints' totals.
+    pub fn check_checkpoints(&self, distance: u64, duration: u64) -> Result<()> {
+        if self.checkpoints > 0 && (self.checkpoint_distance, self.checkpoint_duration) != (distance, duration) {
+            return Err(DriverTripRewardError::CheckpointMismatch.into());
+        }
         Ok(())
     }
 
-    /// Distribute rewards to a driver
-    pub fn distribute_reward(ctx: Context<DistributeReward>, pool_id: u64) -> Result<()> {
-        if ctx.accounts.reward_pool_account.pool_id != pool_id {
-            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+    /// Scores each leg on its own metrics, with the fare split by distance,
+    /// and returns the distance-weighted trip score.
+    pub fn score_legs(&mut self, weights: &ScoringWeights, rating: f32) -> Result<u32> {
+        let (total_distance, _) = self.leg_totals()?;
+        if total_distance == 0 {
+            return Err(DriverTripRewardError::InvalidLeg.into());
         }
-        if !ctx.accounts.reward_pool_account.is_active {
-            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        let mut weighted_score: u128 = 0;
+        for leg in self.legs.iter_mut() {
+            let leg_fare = (self.fare as u128 * leg.distance as u128 / total_distance as u128) as u64;
+            leg.score = weights.score(rating, leg.distance, leg.duration()?, leg_fare);
+            weighted_score = weighted_score.checked_add(leg.score as u128 * leg.distance as u128).ok_or(DriverTripRewardError::InvalidLeg)?;
         }
-        if ctx.accounts.reward_pool_account.distributed_rewards >= ctx.accounts.reward_pool_account.total_rewards {
-            return Err(DriverTripRewardError::InsufficientRewards.into());
+        Ok((weighted_score / total_distance as u128) as u32)
+    }
+
+    /// Next link of the telemetry hash chain: sha256(chain || chunk_index || chunk_hash).
+    pub fn next_telemetry_chain(chain: &[u8; 32], chunk_index: u32, chunk_hash: &[u8; 32]) -> [u8; 32] {
+        hashv(&[chain.as_ref(), &chunk_index.to_le_bytes(), chunk_hash.as_ref()]).to_bytes()
+    }
+}
+
+/// Score penalty per unsafe driving category, and how many reports put a
+/// driver under review.
+#[account]
+pub struct SafetyConfig {
+    pub penalties: [u32; UNSAFE_DRIVING_CATEGORIES],
+    pub review_threshold: u32,
+    pub bump: u8,
+}
+
+impl SafetyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.penalties.iter().any(|penalty| *penalty > 100) || self.review_threshold == 0 {
+            return Err(DriverTripRewardError::InvalidSafetyConfig.into());
         }
-        if ctx.accounts.driver_account.total_trips == 0 {
-            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        Ok(())
+    }
+}
+
+/// A passenger's report of unsafe driving on a trip; one per trip.
+#[account]
+pub struct UnsafeDrivingReport {
+    pub trip_account: Pubkey,
+    pub driver_pubkey: Pubkey,
+    pub passenger_pubkey: Pubkey,
+    pub category: u8,
+    pub evidence_hash: [u8; 32],
+    pub penalty: u32,
+    pub reported_at: i64,
+    pub bump: u8,
+}
+
+/// A passenger's on-chain history. `reliability` (0-1) weights the ratings they
+/// give, dropping for passengers who mostly hand out 1-star ratings.
+#[account]
+pub struct PassengerAccount {
+    pub passenger_pubkey: Pubkey,
+    pub trips_taken: u32,
+    pub ratings_given: u32,
+    pub low_ratings_given: u32,
+    pub disputes_opened: u32,
+    pub reliability: f32,
+    pub bump: u8,
+}
+
+impl PassengerAccount {
+    /// Counts a rating and returns the weight it carries: the passenger's
+    /// reliability including this rating.
+    pub fn record_rating(&mut self, rating: u8) -> f32 {
+        self.ratings_given += 1;
+        if rating == 1 {
+            self.low_ratings_given += 1;
         }
+        let fair_ratings = self.ratings_given - self.low_ratings_given + RELIABILITY_PRIOR_RATINGS;
+        self.reliability = fair_ratings as f32 / (self.ratings_given + RELIABILITY_PRIOR_RATINGS) as f32;
+        self.reliability
+    }
+}
 
-        let reward_amount = ctx.accounts.reward_pool_account.reward_per_trip;
-        if ctx.accounts.reward_pool_account.distributed_rewards + reward_amount > ctx.accounts.reward_pool_account.total_rewards {
-            return Err(DriverTripRewardError::InsufficientRewards.into());
+/// Anti-replay marker for a verified `trip_hash`, pointing back to the trip it
+/// was first verified for.
+#[account]
+pub struct TripHashRecord {
+    pub trip_hash: [u8; 32],
+    pub trip_account: Pubkey,
+    pub driver_pubkey: Pubkey,
+    pub trip_id: u64,
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ValidatorAccount {
+    pub validator_pubkey: Pubkey,
+    pub public_key: String,
+    pub private_key: String,
+    pub is_active: bool,
+    pub bump: u8,
+    pub total_validations: u64,
+    pub success_rate: f32,
+    pub last_validation_time: i64,
+    pub validator_weight: f32,
+}
+
+/// Fare a passenger deposited for a trip. Lamport fares are held by this
+/// account; token fares (`mint` set) by its associated token account.
+#[account]
+pub struct FareEscrow {
+    pub trip_account: Pubkey,
+    pub driver_pubkey: Pubkey,
+    pub passenger_pubkey: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub deposited_at: i64,
+    pub bump: u8,
+}
+
+/// Validator fees earned from one pool and not yet claimed.
+#[account]
+pub struct ValidatorFeeBalance {
+    pub reward_pool: Pubkey,
+    pub validator_pubkey: Pubkey,
+    pub claimable: u64,
+    pub total_earned: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+// -----------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------
+/// Checks that `authority` may act for `driver`: either the driver itself, or a
+/// session key whose `session` account is unexpired and grants `scope`. The
+/// account constraints bind `session` to the (driver, authority) pair.
+pub fn authorize_driver(driver: &Pubkey, authority: &Pubkey, session: &Option<Account<SessionAccount>>, scope: u32, now: i64) -> Result<()> {
+    if authority == driver {
+        return Ok(());
+    }
+    let Some(session) = session else {
+        return Err(DriverTripRewardError::InvalidSessionAuthority.into());
+    };
+    if now >= session.expires_at {
+        return Err(DriverTripRewardError::SessionExpired.into());
+    }
+    if session.allowed_instructions & scope == 0 {
+        return Err(DriverTripRewardError::SessionScopeNotAllowed.into());
+    }
+    Ok(())
+}
+
+/// Input checks shared by `submit_trip` and `submit_next_trip`.
+pub fn check_submitted_trip(start_time: i64, distance: u64, duration: u64, fare: u64) -> Result<()> {
+    if start_time <= 0 {
+        return Err(DriverTripRewardError::InvalidTripStatus.into());
+    }
+    if distance == 0 {
+        return Err(DriverTripRewardError::InvalidTripStatus.into());
+    }
+    if duration == 0 {
+        return Err(DriverTripRewardError::InvalidTripStatus.into());
+    }
+    if fare == 0 {
+        return Err(DriverTripRewardError::InvalidTripStatus.into());
+    }
+    Ok(())
+}
+
+/// Moves `rent` lamports from the sponsor treasury back to the fee payer that
+/// funded an `init`, charging it against the driver's sponsored rent budget.
+/// The treasury must stay rent exempt after the transfer.
+pub fn sponsor_rent<'info>(treasury: &mut Account<'info, SponsorTreasury>, driver_account: &mut DriverAccount, fee_payer: &AccountInfo<'info>, rent: u64) -> Result<()> {
+    if !treasury.is_active {
+        return Err(DriverTripRewardError::SponsorTreasuryInactive.into());
+    }
+    if fee_payer.key() != treasury.relayer {
+        return Err(DriverTripRewardError::SponsorRelayerRequired.into());
+    }
+    let outstanding = driver_account.sponsored_rent.checked_add(rent).ok_or(DriverTripRewardError::SponsorBudgetExceeded)?;
+    if outstanding > treasury.per_driver_budget {
+        return Err(DriverTripRewardError::SponsorBudgetExceeded.into());
+    }
+    let treasury_info = treasury.to_account_info();
+    let min_balance = Rent::get()?.minimum_balance(treasury_info.data_len());
+    if treasury_info.lamports() < min_balance.saturating_add(rent) {
+        return Err(DriverTripRewardError::SponsorTreasuryInsufficient.into());
+    }
+    treasury_info.sub_lamports(rent)?;
+    fee_payer.add_lamports(rent)?;
+    driver_account.sponsored_rent = outstanding;
+    treasury.total_sponsored += rent;
+    Ok(())
+}
+
+/// Credits `amount` lamports to `recipient`, which must end up rent exempt.
+/// Checking first gives a program error instead of the runtime's rent failure.
+pub fn pay_lamports(recipient: &AccountInfo, amount: u64, rent: &Rent) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    if !rent.is_exempt(recipient.lamports().saturating_add(amount), recipient.data_len()) {
+        return Err(DriverTripRewardError::RecipientNotRentExempt.into());
+    }
+    recipient.add_lamports(amount)?;
+    Ok(())
+}
+
+/// Rejects Token-2022 reward mints with extensions outside
+/// `SUPPORTED_MINT_EXTENSIONS`. SPL Token mints have no extensions.
+pub fn check_reward_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+            msg!("Unsupported reward mint extension: {:?}", extension);
+            return Err(DriverTripRewardError::UnsupportedMintExtension.into());
+        }
+    }
+    Ok(())
+}
+
+/// Fare mints follow the reward mint rules but may not charge transfer fees:
+/// settlement closes the fare vault, which Token-2022 refuses while fees are withheld.
+pub fn check_fare_mint(mint: &AccountInfo) -> Result<()> {
+    check_reward_mint(mint)?;
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    if state.get_extension_types()?.contains(&ExtensionType::TransferFeeConfig) {
+        return Err(DriverTripRewardError::InvalidFareEscrow.into());
+    }
+    Ok(())
+}
+
+/// Pays a trip's escrowed fare to `recipient`, or for token fares to
+/// `recipient_token_account`, then closes the escrow and its fare vault back
+/// to the passenger that funded them.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_fare<'info>(
+    escrow: &Account<'info, FareEscrow>,
+    passenger: &Option<UncheckedAccount<'info>>,
+    recipient: &AccountInfo<'info>,
+    fare_mint: &Option<InterfaceAccount<'info, Mint>>,
+    fare_vault: &Option<InterfaceAccount<'info, TokenAccount>>,
+    recipient_token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+) -> Result<u64> {
+    let Some(passenger) = passenger else {
+        return Err(DriverTripRewardError::InvalidFareEscrow.into());
+    };
+    if passenger.key() != escrow.passenger_pubkey {
+        return Err(DriverTripRewardError::InvalidFareEscrow.into());
+    }
+    let amount = escrow.amount;
+    if escrow.mint == Pubkey::default() {
+        escrow.to_account_info().sub_lamports(amount)?;
+        pay_lamports(recipient, amount, &Rent::get()?)?;
+        escrow.close(passenger.to_account_info())?;
+        return Ok(amount);
+    }
+
+    let (Some(fare_mint), Some(fare_vault), Some(recipient_token_account), Some(token_program)) =
+        (fare_mint, fare_vault, recipient_token_account, token_program)
+    else {
+        return Err(DriverTripRewardError::InvalidFareEscrow.into());
+    };
+    if fare_mint.key() != escrow.mint
+        || fare_vault.key() != get_associated_token_address_with_program_id(&escrow.key(), &escrow.mint, &token_program.key())
+        || recipient_token_account.owner != recipient.key()
+        || recipient_token_account.mint != escrow.mint
+    {
+        return Err(DriverTripRewardError::InvalidFareEscrow.into());
+    }
+    let seeds = &[b"fare_escrow", escrow.trip_account.as_ref(), &[escrow.bump]];
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+    let cpi_accounts = TransferChecked {
+        from: fare_vault.to_account_info(),
+        mint: fare_mint.to_account_info(),
+        to: recipient_token_account.to_account_info(),
+        authority: escrow.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, amount, fare_mint.decimals)?;
+    let cpi_accounts = CloseAccount {
+        account: fare_vault.to_account_info(),
+        destination: passenger.to_account_info(),
+        authority: escrow.to_account_info(),
+    };
+    close_account(CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds))?;
+    escrow.close(passenger.to_account_info())?;
+    Ok(amount)
+}
+
+// -----------------------------------------------------------------
+// Program Logic (#[program])
+// -----------------------------------------------------------------
+#[program]
+pub mod driver_trip_reward {
+    use super::*;
+
+    /// Initialize a new driver account
+    pub fn initialize_driver(ctx: Context<InitializeDriver>) -> Result<()> {
+        if ctx.accounts.driver_account.driver_pubkey != Pubkey::default() {
+            return Err(DriverTripRewardError::DriverAlreadyExists.into());
+        }
+        ctx.accounts.driver_account.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.driver_account.total_trips = 0;
+        ctx.accounts.driver_account.total_earnings = 0;
+        ctx.accounts.driver_account.total_distance = 0;
+        ctx.accounts.driver_account.total_time = 0;
+        ctx.accounts.driver_account.rating = 0.0;
+        ctx.accounts.driver_account.is_active = true;
+        ctx.accounts.driver_account.bump = ctx.bumps.driver_account;
+        ctx.accounts.driver_account.total_score = 0;
+        ctx.accounts.driver_account.completed_trips = 0;
+        ctx.accounts.driver_account.avg_rating = 0.0;
+        ctx.accounts.driver_account.total_rewards = 0;
+        ctx.accounts.driver_account.validator_pubkey = Pubkey::default();
+        ctx.accounts.driver_account.last_trip_time = 0;
+        ctx.accounts.driver_account.suspension_reason = 0;
+        ctx.accounts.driver_account.suspended_until = 0;
+        ctx.accounts.driver_account.suspension_count = 0;
+        ctx.accounts.driver_account.sponsored_rent = 0;
+        ctx.accounts.driver_account.rent_payer = ctx.accounts.fee_payer.key();
+        ctx.accounts.driver_account.trip_history = [0u8; 32];
+        ctx.accounts.driver_account.closed_trips = 0;
+        ctx.accounts.driver_account.next_trip_nonce = 0;
+        ctx.accounts.driver_account.active_trip_id = 0;
+        ctx.accounts.driver_account.active_trip_opened_at = 0;
+        ctx.accounts.driver_account.last_counted_end_time = 0;
+        ctx.accounts.driver_account.has_reward_split = false;
+        ctx.accounts.driver_account.rated_trips = 0;
+        ctx.accounts.driver_account.passenger_rating_total = 0.0;
+        ctx.accounts.driver_account.unsafe_reports = 0;
+        ctx.accounts.driver_account.reports_since_review = 0;
+        ctx.accounts.driver_account.under_review = false;
+        ctx.accounts.driver_account.penalty_owed = 0;
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            let rent = ctx.accounts.driver_account.to_account_info().lamports();
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), rent)?;
+            ctx.accounts.driver_account.rent_payer = sponsor_treasury.key();
         }
+        Ok(())
+    }
 
-        ctx.accounts.reward_pool_account.distributed_rewards += reward_amount;
-        ctx.accounts.reward_pool_account.total_trip_rewards += reward_amount;
+    /// Initialize a passenger account
+    pub fn initialize_passenger(ctx: Context<InitializePassenger>) -> Result<()> {
+        ctx.accounts.passenger_account.passenger_pubkey = ctx.accounts.passenger_pubkey.key();
+        ctx.accounts.passenger_account.trips_taken = 0;
+        ctx.accounts.passenger_account.ratings_given = 0;
+        ctx.accounts.passenger_account.low_ratings_given = 0;
+        ctx.accounts.passenger_account.disputes_opened = 0;
+        ctx.accounts.passenger_account.reliability = 1.0;
+        ctx.accounts.passenger_account.bump = ctx.bumps.passenger_account;
+        Ok(())
+    }
 
-        msg!("Distributed {} rewards to driver {}", reward_amount, ctx.accounts.driver_pubkey.key());
+    /// Submit a new trip for verification
+    pub fn submit_trip(ctx: Context<SubmitTrip>, passenger_pubkey: Pubkey, trip_id: u64, start_time: i64, distance: u64, duration: u64, fare: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_SUBMIT_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        check_submitted_trip(start_time, distance, duration, fare)?;
+        ctx.accounts.trip_rules.check_timestamp(start_time, now)?;
+        DriverAccount::check_legacy_trip_id(trip_id)?;
+        ctx.accounts.driver_account.begin_trip(trip_id, now, ctx.accounts.trip_rules.max_trip_age)?;
+        ctx.accounts.trip_account.open(trip_id, ctx.accounts.driver_pubkey.key(), passenger_pubkey, start_time, ctx.accounts.fee_payer.key(), ctx.bumps.trip_account);
+        ctx.accounts.trip_account.distance = distance;
+        ctx.accounts.trip_account.duration = duration;
+        ctx.accounts.trip_account.fare = fare;
+        ctx.accounts.trip_account.passenger_signed = ctx.accounts.passenger.is_some();
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            let rent = ctx.accounts.trip_account.to_account_info().lamports();
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), rent)?;
+            ctx.accounts.trip_account.rent_payer = sponsor_treasury.key();
+        }
         Ok(())
     }
+
+    /// Verify a trip and calculate trip score, optionally binding it to the
+    /// reward pool whose validators may finalize it
+    pub fn verify_trip(ctx: Context<VerifyTrip>, trip_id: u64, end_time: i64, rating: f32, trip_hash: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_VERIFY_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.reward_pool_account.as_ref().is_some_and(|pool| !pool.is_active) {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        if ctx.accounts.trip_account.status != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if end_time <= ctx.accounts.trip_account.start_time {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(end_time, now)?;
+        if !(0.0..=5.0).contains(&rating) {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_trip(ctx.accounts.trip_account.distance, ctx.accounts.trip_account.duration)?;
+        let (distance, duration) = ctx.accounts.trip_account.totals_from_legs(ctx.accounts.trip_account.distance, ctx.accounts.trip_account.duration)?;
+        ctx.accounts.trip_account.distance = distance;
+        ctx.accounts.trip_account.duration = duration;
+        if ctx.accounts.trip_account.has_fare_escrow {
+            return Err(DriverTripRewardError::FareEscrowRequired.into());
+        }
+        if ctx.accounts.trip_account.telemetry_chunks == 0 {
+            return Err(DriverTripRewardError::TelemetryMissing.into());
+        }
+        if trip_hash != ctx.accounts.trip_account.telemetry_chain {
+            return Err(DriverTripRewardError::InvalidTripHash.into());
+        }
+        if ctx.accounts.trip_hash_record.trip_account != Pubkey::default() {
+            msg!("Trip hash already verified for trip {}", ctx.accounts.trip_hash_record.trip_account);
+            return Err(DriverTripRewardError::TripHashReplayed.into());
+        }
+        ctx.accounts.driver_account.finish_trip(trip_id, ctx.accounts.trip_account.start_time, end_time)?;
+
+        ctx.accounts.trip_hash_record.trip_hash = trip_hash;
+        ctx.accounts.trip_hash_record.trip_account = ctx.accounts.trip_account.key();
+        ctx.accounts.trip_hash_record.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.trip_hash_record.trip_id = trip_id;
+        ctx.accounts.trip_hash_record.recorded_at = now;
+        ctx.accounts.trip_hash_record.bump = ctx.bumps.trip_hash_record;
+
+        let score = if ctx.accounts.trip_account.legs.is_empty() {
+            ctx.accounts.scoring_config.weights.score(
+                rating,
+                ctx.accounts.trip_account.distance,
+                ctx.accounts.trip_account.duration,
+                ctx.accounts.trip_account.fare,
+            )
+        } else {
+            ctx.accounts.trip_account.score_legs(&ctx.accounts.scoring_config.weights, rating)?
+        };
+
+        ctx.accounts.trip_account.end_time = end_time;
+        ctx.accounts.trip_account.rating = rating;
+        ctx.accounts.trip_account.score = score;
+        ctx.accounts.trip_account.scoring_version = ctx.accounts.scoring_config.version;
+        ctx.accounts.trip_account.trip_hash = trip_hash;
+        ctx.accounts.trip_account.verification_status = 1;
+        ctx.accounts.trip_account.status = 1;
+        ctx.accounts.trip_account.validator_pubkey = Pubkey::default();
+        if let Some(reward_pool_account) = &ctx.accounts.reward_pool_account {
+            ctx.accounts.trip_account.reward_pool = reward_pool_account.key();
+        }
+
+        ctx.accounts.driver_account.total_trips += 1;
+        ctx.accounts.driver_account.completed_trips += 1;
+        ctx.accounts.driver_account.total_distance += ctx.accounts.trip_account.distance;
+        ctx.accounts.driver_account.total_time += ctx.accounts.trip_account.duration;
+        ctx.accounts.driver_account.add_score(score as u64);
+
+        // The driver's own rating only feeds the score; `rating` comes from passengers.
+        ctx.accounts.driver_account.refresh_rating();
+
+        ctx.accounts.driver_account.last_trip_time = end_time;
+        Ok(())
+    }
+
+    /// Initialize a new reward pool
+    pub fn initialize_reward_pool(ctx: Context<InitializeRewardPool>, pool_id: u64, total_rewards: u64, reward_per_trip: u64, start_time: i64, end_time: i64) -> Result<()> {
+        check_reward_mint(&ctx.accounts.reward_mint.to_account_info())?;
+        let vault = ctx.accounts.vault.key();
+        ctx.accounts.reward_pool_account.open(pool_id, total_rewards, reward_per_trip, start_time, end_time, vault, false, ctx.bumps.reward_pool_account)?;
+
+        msg!("✅ Reward pool initialized with vault: {}", ctx.accounts.vault.key());
+        Ok(())
+    }
+
+    /// Initialize a reward pool paying native SOL from a lamport vault PDA
+    pub fn initialize_sol_reward_pool(ctx: Context<InitializeSolRewardPool>, pool_id: u64, total_rewards: u64, reward_per_trip: u64, start_time: i64, end_time: i64) -> Result<()> {
+        let vault = ctx.accounts.sol_vault.key();
+        ctx.accounts.reward_pool_account.open(pool_id, total_rewards, reward_per_trip, start_time, end_time, vault, true, ctx.bumps.reward_pool_account)?;
+        ctx.accounts.sol_vault.reward_pool = ctx.accounts.reward_pool_account.key();
+        ctx.accounts.sol_vault.bump = ctx.bumps.sol_vault;
+
+        msg!("✅ SOL reward pool initialized with vault: {}", vault);
+        Ok(())
+    }
+
+    /// Deposit lamports into a SOL reward pool's vault
+    pub fn fund_sol_reward_pool(ctx: Context<FundSolRewardPool>, pool_id: u64, amount: u64) -> Result<()> {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.sol_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+
+        msg!("SOL reward pool {} funded with {} lamports", pool_id, amount);
+        Ok(())
+    }
+
+    /// Claim SOL rewards for completed trips
+    pub fn claim_sol_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimSolRewards<'info>>, pool_id: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        if ctx.accounts.driver_account.has_reward_split && ctx.accounts.reward_split.is_none() {
+            return Err(DriverTripRewardError::InvalidRewardSplit.into());
+        }
+        let reward_pool = ctx.accounts.reward_pool_account.key();
+        let reward_amount = ctx.accounts.reward_pool_account.check_claim(
+            pool_id,
+            reward_pool,
+            &ctx.accounts.driver_account,
+            ctx.accounts.trip_account.as_deref(),
+            now,
+        )?;
+
+        let driver = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.driver_pool_state.bind(reward_pool, driver, ctx.bumps.driver_pool_state);
+        ctx.accounts.driver_pool_state.record_claim(&ctx.accounts.reward_pool_account, reward_amount, now, clock.epoch)?;
+
+        let rent = Rent::get()?;
+        let vault_info = ctx.accounts.sol_vault.to_account_info();
+        let min_balance = rent.minimum_balance(vault_info.data_len());
+        if vault_info.lamports() < min_balance.saturating_add(reward_amount) {
+            return Err(DriverTripRewardError::SolVaultInsufficient.into());
+        }
+        let protocol_fee = ctx.accounts.config.protocol_fee(reward_amount);
+        let net_amount = reward_amount - protocol_fee;
+        vault_info.sub_lamports(reward_amount)?;
+        if protocol_fee > 0 {
+            let Some(treasury) = &ctx.accounts.treasury else {
+                return Err(DriverTripRewardError::InvalidTreasury.into());
+            };
+            pay_lamports(treasury, protocol_fee, &rent)?;
+        }
+        let mut donated = 0;
+        if let Some(reward_split) = &mut ctx.accounts.reward_split {
+            reward_split.check_accounts(ctx.remaining_accounts, |wallet| *wallet)?;
+            for (beneficiary, account) in reward_split.beneficiaries.iter_mut().zip(ctx.remaining_accounts) {
+                let share = beneficiary.share_of(net_amount);
+                pay_lamports(account, share, &rent)?;
+                beneficiary.total_donated = beneficiary.total_donated.saturating_add(share);
+                donated += share;
+            }
+            reward_split.total_donated = reward_split.total_donated.saturating_add(donated);
+        }
+        let received = net_amount - donated;
+        pay_lamports(&ctx.accounts.destination, received, &rent)?;
+
+        let payout = Payout { reward_amount, protocol_fee, donated, received };
+        ctx.accounts.reward_pool_account.record_payout(
+            &mut ctx.accounts.driver_account,
+            ctx.accounts.trip_account.as_deref_mut(),
+            &payout,
+            now,
+        );
+        ctx.accounts.driver_pool_state.record_payout(&payout);
+
+        msg!(
+            "✅ Claimed {} lamports: {} protocol fee, {} donated, {} received by {} for driver {}",
+            reward_amount,
+            protocol_fee,
+            donated,
+            received,
+            ctx.accounts.destination.key(),
+            driver
+        );
+        Ok(())
+    }
+
+    /// Initialize a new validator account
+    pub fn initialize_validator(ctx: Context<InitializeValidator>, public_key: String, private_key: String) -> Result<()> {
+        if ctx.accounts.validator_account.validator_pubkey != Pubkey::default() {
+            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+        }
+        if public_key.len() < 10 || public_key.len() > 1000 {
+            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+        }
+        if private_key.len() < 10 || private_key.len() > 1000 {
+            return Err(DriverTripRewardError::ValidatorAlreadyExists.into());
+        }
+        ctx.accounts.validator_account.validator_pubkey = ctx.accounts.validator_pubkey.key();
+        ctx.accounts.validator_account.public_key = public_key;
+        ctx.accounts.validator_account.private_key = private_key;
+        // Inactive until the admin approves it with `set_validator_active`.
+        ctx.accounts.validator_account.is_active = false;
+        ctx.accounts.validator_account.bump = ctx.bumps.validator_account;
+        ctx.accounts.validator_account.total_validations = 0;
+        ctx.accounts.validator_account.success_rate = 0.0;
+        ctx.accounts.validator_account.last_validation_time = 0;
+        ctx.accounts.validator_account.validator_weight = 1.0;
+        Ok(())
+    }
+
+    /// Approve or deactivate a validator
+    pub fn set_validator_active(ctx: Context<SetValidatorActive>, is_active: bool) -> Result<()> {
+        ctx.accounts.validator_account.is_active = is_active;
+        msg!("Validator {} active: {}", ctx.accounts.validator_account.validator_pubkey, is_active);
+        Ok(())
+    }
+
+    /// Process private data
+    pub fn process_private_data(ctx: Context<ProcessPrivateData>, data: String, operation: String) -> Result<()> {
+        if operation != "encrypt" && operation != "decrypt" && operation != "hash" {
+            return Err(DriverTripRewardError::InvalidDataOperation.into());
+        }
+        if data.len() > 10000 {
+            return Err(DriverTripRewardError::InvalidDataOperation.into());
+        }
+        ctx.accounts.validator_account.total_validations += 1;
+        ctx.accounts.validator_account.last_validation_time = Clock::get().unwrap().unix_timestamp;
+
+        msg!("Processing private data with operation: {}", operation);
+        msg!("Data length: {}", data.len());
+        Ok(())
+    }
+
+    /// Claim rewards for completed trips
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>, pool_id: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        if ctx.accounts.driver_account.has_reward_split && ctx.accounts.reward_split.is_none() {
+            return Err(DriverTripRewardError::InvalidRewardSplit.into());
+        }
+        let reward_pool = ctx.accounts.reward_pool_account.key();
+        let reward_amount = ctx.accounts.reward_pool_account.check_claim(
+            pool_id,
+            reward_pool,
+            &ctx.accounts.driver_account,
+            ctx.accounts.trip_account.as_deref(),
+            now,
+        )?;
+
+        ctx.accounts.driver_pool_state.bind(reward_pool, ctx.accounts.driver_pubkey.key(), ctx.bumps.driver_pool_state);
+        ctx.accounts.driver_pool_state.record_claim(&ctx.accounts.reward_pool_account, reward_amount, now, clock.epoch)?;
+
+        let protocol_fee = ctx.accounts.config.protocol_fee(reward_amount);
+        let net_amount = reward_amount - protocol_fee;
+
+        let seeds = &[
+            b"vault_authority",
+            ctx.accounts.reward_pool_account.to_account_info().key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+		let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+		// decimals needed for transfer_checked
+		let decimals = ctx.accounts.reward_mint.decimals;
+
+		if protocol_fee > 0 {
+			let Some(treasury_token_account) = &ctx.accounts.treasury_token_account else {
+				return Err(DriverTripRewardError::InvalidTreasury.into());
+			};
+			let cpi_accounts = TransferChecked {
+				from: ctx.accounts.vault.to_account_info(),
+				mint: ctx.accounts.reward_mint.to_account_info(),
+				to: treasury_token_account.to_account_info(),
+				authority: ctx.accounts.vault_authority.to_account_info(),
+			};
+			let cpi_program = ctx.accounts.token_program.to_account_info();
+			transfer_checked(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), protocol_fee, decimals)?;
+		}
+
+		let mut donated = 0;
+		if let Some(reward_split) = &mut ctx.accounts.reward_split {
+			let mint = ctx.accounts.reward_mint.key();
+			let token_program = ctx.accounts.token_program.key();
+			reward_split.check_accounts(ctx.remaining_accounts, |wallet| {
+				get_associated_token_address_with_program_id(wallet, &mint, &token_program)
+			})?;
+			for (beneficiary, account) in reward_split.beneficiaries.iter_mut().zip(ctx.remaining_accounts) {
+				let share = beneficiary.share_of(net_amount);
+				if share == 0 {
+					continue;
+				}
+				let cpi_accounts = TransferChecked {
+					from: ctx.accounts.vault.to_account_info(),
+					mint: ctx.accounts.reward_mint.to_account_info(),
+					to: account.clone(),
+					authority: ctx.accounts.vault_authority.to_account_info(),
+				};
+				let cpi_program = ctx.accounts.token_program.to_account_info();
+				transfer_checked(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), share, decimals)?;
+				beneficiary.total_donated = beneficiary.total_donated.saturating_add(share);
+				donated += share;
+			}
+			reward_split.total_donated = reward_split.total_donated.saturating_add(donated);
+		}
+
+		// must use TransferChecked and include mint
+		let cpi_accounts = TransferChecked {
+			from: ctx.accounts.vault.to_account_info(),
+			mint: ctx.accounts.reward_mint.to_account_info(),
+			to: ctx.accounts.driver_token_account.to_account_info(),
+			authority: ctx.accounts.vault_authority.to_account_info(),
+		};
+		let cpi_program = ctx.accounts.token_program.to_account_info();
+		let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+		let balance_before = ctx.accounts.driver_token_account.amount;
+		transfer_checked(cpi_ctx, net_amount - donated, decimals)?;
+
+		// Token-2022 transfer fees are withheld in the destination account, so
+		// the driver receives less than `reward_amount`.
+		ctx.accounts.driver_token_account.reload()?;
+		let received = ctx.accounts.driver_token_account.amount.saturating_sub(balance_before);
+		let payout = Payout { reward_amount, protocol_fee, donated, received };
+		ctx.accounts.reward_pool_account.record_payout(
+			&mut ctx.accounts.driver_account,
+			ctx.accounts.trip_account.as_deref_mut(),
+			&payout,
+			now,
+		);
+		ctx.accounts.driver_pool_state.record_payout(&payout);
+
+		msg!(
+			"✅ Claimed {} tokens: {} protocol fee, {} donated, {} received by {} for driver {}",
+			reward_amount,
+			protocol_fee,
+			donated,
+			received,
+			ctx.accounts.destination_owner.key(),
+			ctx.accounts.driver_pubkey.key()
+		);
+		Ok(())
+    }
+
+    /// Update validator weights
+    pub fn update_validator_weights(ctx: Context<UpdateValidatorWeights>, new_weights: Vec<u8>) -> Result<()> {
+        if new_weights.is_empty() || new_weights.len() > 10000 {
+            return Err(DriverTripRewardError::InvalidDataOperation.into());
+        }
+
+        msg!("Validator weights updated with {} bytes", new_weights.len());
+
+        ctx.accounts.validator_account.total_validations += 1;
+        ctx.accounts.validator_account.last_validation_time = Clock::get().unwrap().unix_timestamp;
+        Ok(())
+    }
+
+    /// Complete a trip and update driver statistics
+    pub fn complete_trip<'info>(ctx: Context<'_, '_, 'info, 'info, CompleteTrip<'info>>, trip_id: u64, end_time: i64, distance: u64, duration: u64, fare: u64, rating: f32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_COMPLETE_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.status != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if end_time <= ctx.accounts.trip_account.start_time {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(end_time, now)?;
+        if !(0.0..=5.0).contains(&rating) {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        let (distance, duration) = ctx.accounts.trip_account.totals_from_legs(distance, duration)?;
+        ctx.accounts.trip_rules.check_trip(distance, duration)?;
+        ctx.accounts.trip_account.check_checkpoints(distance, duration)?;
+        // The trip ends now; the client's end_time is only sanity-checked.
+        let end_time = now;
+        if end_time <= ctx.accounts.trip_account.start_time {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.driver_account.finish_trip(trip_id, ctx.accounts.trip_account.start_time, end_time)?;
+
+        // Only a fare released from escrow counts towards the driver's earnings.
+        let mut settled_fare = 0;
+        if ctx.accounts.trip_account.has_fare_escrow {
+            let Some(fare_escrow) = &ctx.accounts.fare_escrow else {
+                return Err(DriverTripRewardError::FareEscrowRequired.into());
+            };
+            if fare != fare_escrow.amount {
+                return Err(DriverTripRewardError::InvalidFareEscrow.into());
+            }
+            let Some(passenger_account) = &mut ctx.accounts.passenger_account else {
+                return Err(DriverTripRewardError::PassengerAccountRequired.into());
+            };
+            if passenger_account.passenger_pubkey != fare_escrow.passenger_pubkey {
+                return Err(DriverTripRewardError::InvalidFareEscrow.into());
+            }
+            passenger_account.trips_taken += 1;
+            settled_fare = settle_fare(
+                fare_escrow,
+                &ctx.accounts.passenger,
+                &ctx.accounts.driver_pubkey.to_account_info(),
+                &ctx.accounts.fare_mint,
+                &ctx.accounts.fare_vault,
+                &ctx.accounts.fare_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        ctx.accounts.trip_account.end_time = end_time;
+        ctx.accounts.trip_account.distance = distance;
+        ctx.accounts.trip_account.duration = duration;
+        ctx.accounts.trip_account.fare = fare;
+        ctx.accounts.trip_account.rating = rating;
+        ctx.accounts.trip_account.status = 1;
+        // Each leg's passenger gets a leg score; the trip itself stays unscored.
+        if !ctx.accounts.trip_account.legs.is_empty() {
+            ctx.accounts.trip_account.score_legs(&ctx.accounts.scoring_config.weights, rating)?;
+            ctx.accounts.trip_account.scoring_version = ctx.accounts.scoring_config.version;
+        }
+
+        ctx.accounts.driver_account.total_trips += 1;
+        ctx.accounts.driver_account.total_earnings += settled_fare;
+        ctx.accounts.driver_account.total_distance += distance;
+        ctx.accounts.driver_account.total_time += duration;
+
+        // The driver's own rating only feeds the score; `rating` comes from passengers.
+        ctx.accounts.driver_account.refresh_rating();
+
+        ctx.accounts.driver_account.last_trip_time = end_time;
+        Ok(())
+    }
+
+    /// Create a new trip account
+    pub fn create_trip(ctx: Context<CreateTrip>, passenger_pubkey: Pubkey, trip_id: u64, start_time: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CREATE_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if start_time <= 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(start_time, now)?;
+
+        // The trip starts now; the client's start_time is only sanity-checked.
+        DriverAccount::check_legacy_trip_id(trip_id)?;
+        ctx.accounts.driver_account.begin_trip(trip_id, now, ctx.accounts.trip_rules.max_trip_age)?;
+        ctx.accounts.trip_account.open(trip_id, ctx.accounts.driver_pubkey.key(), passenger_pubkey, now, ctx.accounts.fee_payer.key(), ctx.bumps.trip_account);
+        ctx.accounts.trip_account.passenger_signed = ctx.accounts.passenger.is_some();
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            let rent = ctx.accounts.trip_account.to_account_info().lamports();
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), rent)?;
+            ctx.accounts.trip_account.rent_payer = sponsor_treasury.key();
+        }
+        Ok(())
+    }
+
+    /// Create a new trip account with the passenger's fare deposited into escrow.
+    /// Token fares pass the fare mint and token accounts; lamport fares omit them.
+    pub fn create_trip_with_fare(ctx: Context<CreateTripWithFare>, trip_id: u64, start_time: i64, fare: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CREATE_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if start_time <= 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(start_time, now)?;
+        if fare == 0 || ctx.accounts.passenger.key() == ctx.accounts.driver_pubkey.key() {
+            return Err(DriverTripRewardError::InvalidFareEscrow.into());
+        }
+
+        DriverAccount::check_legacy_trip_id(trip_id)?;
+        ctx.accounts.driver_account.begin_trip(trip_id, now, ctx.accounts.trip_rules.max_trip_age)?;
+        ctx.accounts.trip_account.open(trip_id, ctx.accounts.driver_pubkey.key(), ctx.accounts.passenger.key(), now, ctx.accounts.fee_payer.key(), ctx.bumps.trip_account);
+        ctx.accounts.trip_account.fare = fare;
+        ctx.accounts.trip_account.has_fare_escrow = true;
+        ctx.accounts.trip_account.passenger_signed = true;
+
+        let mint = match (&ctx.accounts.fare_mint, &ctx.accounts.passenger_token_account, &ctx.accounts.fare_vault, &ctx.accounts.token_program) {
+            (Some(fare_mint), Some(passenger_token_account), Some(fare_vault), Some(token_program)) => {
+                check_fare_mint(&fare_mint.to_account_info())?;
+                let cpi_accounts = TransferChecked {
+                    from: passenger_token_account.to_account_info(),
+                    mint: fare_mint.to_account_info(),
+                    to: fare_vault.to_account_info(),
+                    authority: ctx.accounts.passenger.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                transfer_checked(cpi_ctx, fare, fare_mint.decimals)?;
+                fare_mint.key()
+            }
+            (None, None, None, _) => {
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.passenger.to_account_info(),
+                    to: ctx.accounts.fare_escrow.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+                anchor_lang::system_program::transfer(cpi_ctx, fare)?;
+                Pubkey::default()
+            }
+            _ => return Err(DriverTripRewardError::InvalidFareEscrow.into()),
+        };
+
+        ctx.accounts.fare_escrow.trip_account = ctx.accounts.trip_account.key();
+        ctx.accounts.fare_escrow.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.fare_escrow.passenger_pubkey = ctx.accounts.passenger.key();
+        ctx.accounts.fare_escrow.mint = mint;
+        ctx.accounts.fare_escrow.amount = fare;
+        ctx.accounts.fare_escrow.deposited_at = now;
+        ctx.accounts.fare_escrow.bump = ctx.bumps.fare_escrow;
+
+        msg!("Escrowed fare of {} for trip {}", fare, trip_id);
+        Ok(())
+    }
+
+    /// Cancel a pending trip, refunding any escrowed fare to the passenger.
+    /// The passenger may cancel once the trip is older than `max_trip_age`.
+    pub fn cancel_trip<'info>(ctx: Context<'_, '_, 'info, 'info, CancelTrip<'info>>, trip_id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let is_passenger = ctx.accounts.fare_escrow.as_ref().is_some_and(|escrow| escrow.passenger_pubkey == ctx.accounts.authority.key());
+        if is_passenger {
+            if now.saturating_sub(ctx.accounts.trip_account.start_time) <= ctx.accounts.trip_rules.max_trip_age {
+                return Err(DriverTripRewardError::FareEscrowLocked.into());
+            }
+            // Reclaiming a fare the driver never settled counts as a dispute.
+            let Some(passenger_account) = &mut ctx.accounts.passenger_account else {
+                return Err(DriverTripRewardError::PassengerAccountRequired.into());
+            };
+            if passenger_account.passenger_pubkey != ctx.accounts.authority.key() {
+                return Err(DriverTripRewardError::InvalidFareEscrow.into());
+            }
+            passenger_account.disputes_opened += 1;
+        } else {
+            authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CREATE_TRIP, now)?;
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.status != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+
+        let mut refunded = 0;
+        if ctx.accounts.trip_account.has_fare_escrow {
+            let Some(fare_escrow) = &ctx.accounts.fare_escrow else {
+                return Err(DriverTripRewardError::FareEscrowRequired.into());
+            };
+            let Some(passenger) = &ctx.accounts.passenger else {
+                return Err(DriverTripRewardError::InvalidFareEscrow.into());
+            };
+            refunded = settle_fare(
+                fare_escrow,
+                &ctx.accounts.passenger,
+                &passenger.to_account_info(),
+                &ctx.accounts.fare_mint,
+                &ctx.accounts.fare_vault,
+                &ctx.accounts.fare_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Cancelled trips are never counted, so only the active slot is released.
+        ctx.accounts.trip_account.status = 2;
+        if ctx.accounts.driver_account.active_trip_opened_at != 0 && ctx.accounts.driver_account.active_trip_id == trip_id {
+            ctx.accounts.driver_account.active_trip_id = 0;
+            ctx.accounts.driver_account.active_trip_opened_at = 0;
+        }
+
+        msg!("Cancelled trip {}, refunding {}", trip_id, refunded);
+        Ok(())
+    }
+
+    /// Append a stop-to-stop leg to a pending trip, growing its account at the
+    /// expense of whoever paid its rent
+    pub fn add_trip_leg(ctx: Context<AddTripLeg>, trip_id: u64, start_time: i64, end_time: i64, distance: u64, passenger: Option<Pubkey>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CREATE_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.status != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if ctx.accounts.trip_account.legs.len() >= MAX_TRIP_LEGS {
+            return Err(DriverTripRewardError::TooManyLegs.into());
+        }
+        let previous_end = ctx.accounts.trip_account.legs.last().map_or(ctx.accounts.trip_account.start_time, |leg| leg.end_time);
+        if start_time < previous_end || end_time <= start_time || distance == 0 {
+            return Err(DriverTripRewardError::InvalidLeg.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(end_time, now)?;
+        // The grown rent is refunded along with the rest when the trip closes,
+        // so whoever paid the trip's rent pays for the growth too.
+        let rent_payer = ctx.accounts.trip_account.rent_payer;
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            if sponsor_treasury.key() != rent_payer {
+                return Err(DriverTripRewardError::InvalidRentPayer.into());
+            }
+            let rent = Rent::get()?;
+            let data_len = ctx.accounts.trip_account.to_account_info().data_len();
+            let growth = rent.minimum_balance(data_len).saturating_sub(rent.minimum_balance(data_len - TRIP_LEG_SPACE));
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), growth)?;
+        } else if ctx.accounts.fee_payer.key() != rent_payer {
+            return Err(DriverTripRewardError::InvalidRentPayer.into());
+        }
+
+        ctx.accounts.trip_account.legs.push(TripLeg {
+            start_time,
+            end_time,
+            distance,
+            passenger: passenger.unwrap_or_default(),
+            score: 0,
+        });
+
+        msg!("Added leg {} to trip {}", ctx.accounts.trip_account.legs.len(), trip_id);
+        Ok(())
+    }
+
+    /// Rate a counted trip as its passenger, within `PASSENGER_RATING_WINDOW` of its end
+    pub fn rate_trip(ctx: Context<RateTrip>, trip_id: u64, rating: u8) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.passenger_pubkey != ctx.accounts.passenger.key() || ctx.accounts.passenger.key() == ctx.accounts.driver_pubkey.key() {
+            return Err(DriverTripRewardError::Unauthorized.into());
+        }
+        // The driver chooses `passenger_pubkey` freely unless the passenger signed.
+        if !ctx.accounts.trip_account.passenger_signed {
+            return Err(DriverTripRewardError::PassengerSignatureRequired.into());
+        }
+        if ctx.accounts.trip_account.status != 1 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if !(1..=5).contains(&rating) {
+            return Err(DriverTripRewardError::InvalidRating.into());
+        }
+        if ctx.accounts.trip_account.passenger_rating != 0 {
+            return Err(DriverTripRewardError::TripAlreadyRated.into());
+        }
+        if now > ctx.accounts.trip_account.end_time.saturating_add(PASSENGER_RATING_WINDOW) {
+            return Err(DriverTripRewardError::RatingWindowClosed.into());
+        }
+
+        // Ratings from unreliable passengers are pulled towards neutral.
+        let weight = ctx.accounts.passenger_account.record_rating(rating);
+        let weighted_rating = weight * rating as f32 + (1.0 - weight) * NEUTRAL_RATING;
+
+        ctx.accounts.trip_account.passenger_rating = rating;
+        ctx.accounts.trip_account.rated_at = now;
+        ctx.accounts.driver_account.rated_trips += 1;
+        ctx.accounts.driver_account.passenger_rating_total += weighted_rating;
+        ctx.accounts.driver_account.refresh_rating();
+
+        msg!("Passenger rated trip {} with {} stars", trip_id, rating);
+        Ok(())
+    }
+
+    /// Submit a new trip under the driver's next trip nonce
+    pub fn submit_next_trip(ctx: Context<SubmitNextTrip>, passenger_pubkey: Pubkey, start_time: i64, distance: u64, duration: u64, fare: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_SUBMIT_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        check_submitted_trip(start_time, distance, duration, fare)?;
+        ctx.accounts.trip_rules.check_timestamp(start_time, now)?;
+
+        let trip_id = ctx.accounts.driver_account.next_trip_id()?;
+        ctx.accounts.driver_account.begin_trip(trip_id, now, ctx.accounts.trip_rules.max_trip_age)?;
+        ctx.accounts.driver_account.next_trip_nonce += 1;
+        ctx.accounts.trip_account.open(trip_id, ctx.accounts.driver_pubkey.key(), passenger_pubkey, start_time, ctx.accounts.fee_payer.key(), ctx.bumps.trip_account);
+        ctx.accounts.trip_account.distance = distance;
+        ctx.accounts.trip_account.duration = duration;
+        ctx.accounts.trip_account.fare = fare;
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            let rent = ctx.accounts.trip_account.to_account_info().lamports();
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), rent)?;
+            ctx.accounts.trip_account.rent_payer = sponsor_treasury.key();
+        }
+
+        msg!("Submitted trip {} for driver {}", trip_id, ctx.accounts.driver_pubkey.key());
+        Ok(())
+    }
+
+    /// Create a new trip account under the driver's next trip nonce
+    pub fn create_next_trip(ctx: Context<CreateNextTrip>, passenger_pubkey: Pubkey, start_time: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CREATE_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if start_time <= 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        ctx.accounts.trip_rules.check_timestamp(start_time, now)?;
+
+        // The trip starts now; the client's start_time is only sanity-checked.
+        let trip_id = ctx.accounts.driver_account.next_trip_id()?;
+        ctx.accounts.driver_account.begin_trip(trip_id, now, ctx.accounts.trip_rules.max_trip_age)?;
+        ctx.accounts.driver_account.next_trip_nonce += 1;
+        ctx.accounts.trip_account.open(trip_id, ctx.accounts.driver_pubkey.key(), passenger_pubkey, now, ctx.accounts.fee_payer.key(), ctx.bumps.trip_account);
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            let rent = ctx.accounts.trip_account.to_account_info().lamports();
+            sponsor_rent(sponsor_treasury, &mut ctx.accounts.driver_account, &ctx.accounts.fee_payer.to_account_info(), rent)?;
+            ctx.accounts.trip_account.rent_payer = sponsor_treasury.key();
+        }
+
+        msg!("Created trip {} for driver {}", trip_id, ctx.accounts.driver_pubkey.key());
+        Ok(())
+    }
+
+    /// Distribute rewards to a driver
+    pub fn distribute_reward(ctx: Context<DistributeReward>, pool_id: u64) -> Result<()> {
+        if ctx.accounts.driver_account.is_suspended(Clock::get()?.unix_timestamp) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.driver_account.under_review {
+            return Err(DriverTripRewardError::DriverUnderReview.into());
+        }
+        if ctx.accounts.reward_pool_account.pool_id != pool_id {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        if !ctx.accounts.reward_pool_account.is_active {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        if ctx.accounts.reward_pool_account.distributed_rewards >= ctx.accounts.reward_pool_account.total_rewards {
+            return Err(DriverTripRewardError::InsufficientRewards.into());
+        }
+        if ctx.accounts.driver_account.total_trips == 0 {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+
+        let reward_amount = ctx.accounts.reward_pool_account.reward_per_trip;
+        if ctx.accounts.reward_pool_account.distributed_rewards + reward_amount > ctx.accounts.reward_pool_account.total_rewards {
+            return Err(DriverTripRewardError::InsufficientRewards.into());
+        }
+
+        ctx.accounts.reward_pool_account.distributed_rewards += reward_amount;
+        ctx.accounts.reward_pool_account.total_trip_rewards += reward_amount;
+
+        msg!("Distributed {} rewards to driver {}", reward_amount, ctx.accounts.driver_pubkey.key());
+        Ok(())
+    }
+
+    /// Set a pool's per-driver daily and epoch reward caps and claim cooldown
+    pub fn set_pool_claim_limits(ctx: Context<UpdateRewardPool>, pool_id: u64, daily_cap_per_driver: u64, epoch_cap_per_driver: u64, claim_cooldown: i64) -> Result<()> {
+        if ctx.accounts.reward_pool_account.pool_id != pool_id {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        if claim_cooldown < 0 {
+            return Err(DriverTripRewardError::InvalidClaimLimits.into());
+        }
+        ctx.accounts.reward_pool_account.daily_cap_per_driver = daily_cap_per_driver;
+        ctx.accounts.reward_pool_account.epoch_cap_per_driver = epoch_cap_per_driver;
+        ctx.accounts.reward_pool_account.claim_cooldown = claim_cooldown;
+        Ok(())
+    }
+
+    /// Set the fee a pool pays the validator that finalizes a trip
+    pub fn set_validator_fee(ctx: Context<UpdateRewardPool>, pool_id: u64, validator_fee: u64) -> Result<()> {
+        if ctx.accounts.reward_pool_account.pool_id != pool_id {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        ctx.accounts.reward_pool_account.validator_fee = validator_fee;
+        Ok(())
+    }
+
+    /// Finalize a verified trip as a validator, accruing the pool's validator fee
+    pub fn finalize_trip(ctx: Context<FinalizeTrip>, pool_id: u64, trip_id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if !ctx.accounts.validator_account.is_active {
+            return Err(DriverTripRewardError::ValidatorInactive.into());
+        }
+        if ctx.accounts.trip_account.verification_status != 1 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if ctx.accounts.trip_account.validator_pubkey != Pubkey::default() {
+            return Err(DriverTripRewardError::TripAlreadyFinalized.into());
+        }
+        if ctx.accounts.trip_account.reward_pool != ctx.accounts.reward_pool_account.key() {
+            return Err(DriverTripRewardError::TripPoolMismatch.into());
+        }
+        if ctx.accounts.trip_account.driver_pubkey == ctx.accounts.validator_pubkey.key() {
+            return Err(DriverTripRewardError::Unauthorized.into());
+        }
+        if ctx.accounts.reward_pool_account.pool_id != pool_id || !ctx.accounts.reward_pool_account.is_active {
+            return Err(DriverTripRewardError::RewardPoolNotFound.into());
+        }
+        let validator_fee = ctx.accounts.reward_pool_account.validator_fee;
+        if ctx.accounts.reward_pool_account.distributed_rewards.saturating_add(validator_fee) > ctx.accounts.reward_pool_account.total_rewards {
+            return Err(DriverTripRewardError::InsufficientRewards.into());
+        }
+
+        ctx.accounts.trip_account.validator_pubkey = ctx.accounts.validator_pubkey.key();
+        ctx.accounts.validator_account.total_validations += 1;
+        ctx.accounts.validator_account.last_validation_time = now;
+
+        if ctx.accounts.validator_fee_balance.validator_pubkey == Pubkey::default() {
+            ctx.accounts.validator_fee_balance.reward_pool = ctx.accounts.reward_pool_account.key();
+            ctx.accounts.validator_fee_balance.validator_pubkey = ctx.accounts.validator_pubkey.key();
+            ctx.accounts.validator_fee_balance.bump = ctx.bumps.validator_fee_balance;
+        }
+        ctx.accounts.validator_fee_balance.claimable += validator_fee;
+        ctx.accounts.validator_fee_balance.total_earned += validator_fee;
+        ctx.accounts.reward_pool_account.distributed_rewards += validator_fee;
+        ctx.accounts.reward_pool_account.total_validator_fees += validator_fee;
+
+        msg!("Trip {} finalized by validator {} for a fee of {}", trip_id, ctx.accounts.validator_pubkey.key(), validator_fee);
+        Ok(())
+    }
+
+    /// Pay a validator's accrued fees out of a token pool's vault
+    pub fn claim_validator_fees(ctx: Context<ClaimValidatorFees>, pool_id: u64) -> Result<()> {
+        let amount = ctx.accounts.validator_fee_balance.claimable;
+        if amount == 0 {
+            return Err(DriverTripRewardError::NoValidatorFees.into());
+        }
+
+        let seeds = &[
+            b"vault_authority",
+            ctx.accounts.reward_pool_account.to_account_info().key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.reward_mint.to_account_info(),
+            to: ctx.accounts.validator_token_account.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+
+        ctx.accounts.validator_fee_balance.claimable = 0;
+        ctx.accounts.validator_fee_balance.total_claimed += amount;
+
+        msg!("Paid {} in validator fees from pool {} to {}", amount, pool_id, ctx.accounts.validator_pubkey.key());
+        Ok(())
+    }
+
+    /// Pay a validator's accrued fees out of a SOL pool's vault
+    pub fn claim_sol_validator_fees(ctx: Context<ClaimSolValidatorFees>, pool_id: u64) -> Result<()> {
+        let amount = ctx.accounts.validator_fee_balance.claimable;
+        if amount == 0 {
+            return Err(DriverTripRewardError::NoValidatorFees.into());
+        }
+        let vault_info = ctx.accounts.sol_vault.to_account_info();
+        let min_balance = Rent::get()?.minimum_balance(vault_info.data_len());
+        if vault_info.lamports() < min_balance.saturating_add(amount) {
+            return Err(DriverTripRewardError::SolVaultInsufficient.into());
+        }
+        vault_info.sub_lamports(amount)?;
+        ctx.accounts.validator_pubkey.add_lamports(amount)?;
+
+        ctx.accounts.validator_fee_balance.claimable = 0;
+        ctx.accounts.validator_fee_balance.total_claimed += amount;
+
+        msg!("Paid {} lamports in validator fees from pool {} to {}", amount, pool_id, ctx.accounts.validator_pubkey.key());
+        Ok(())
+    }
+
+    /// Initialize the program config and record the admin
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        ctx.accounts.config.admin_pubkey = ctx.accounts.admin_pubkey.key();
+        ctx.accounts.config.bump = ctx.bumps.config;
+        ctx.accounts.config.fee_bps = 0;
+        ctx.accounts.config.treasury = Pubkey::default();
+        Ok(())
+    }
+
+    /// Set the protocol fee taken from claims and the treasury receiving it
+    pub fn set_protocol_fee(ctx: Context<SetProtocolFee>, fee_bps: u16, treasury: Pubkey) -> Result<()> {
+        if fee_bps > MAX_PROTOCOL_FEE_BPS {
+            return Err(DriverTripRewardError::FeeTooHigh.into());
+        }
+        if fee_bps > 0 && treasury == Pubkey::default() {
+            return Err(DriverTripRewardError::InvalidTreasury.into());
+        }
+        ctx.accounts.config.fee_bps = fee_bps;
+        ctx.accounts.config.treasury = treasury;
+        Ok(())
+    }
+
+    /// Set the beneficiaries that receive part of the driver's claims; an
+    /// empty list stops the split
+    pub fn set_reward_split(ctx: Context<SetRewardSplit>, shares: Vec<SplitShare>) -> Result<()> {
+        ctx.accounts.reward_split.set_shares(&shares)?;
+        ctx.accounts.reward_split.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.reward_split.bump = ctx.bumps.reward_split;
+        ctx.accounts.driver_account.has_reward_split = !shares.is_empty();
+
+        msg!("Driver {} splits rewards with {} beneficiaries", ctx.accounts.driver_pubkey.key(), shares.len());
+        Ok(())
+    }
+
+    /// Suspend a driver, optionally until `expires_at`
+    pub fn suspend_driver(ctx: Context<SuspendDriver>, reason_code: u8, expires_at: Option<i64>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        let expires_at = expires_at.unwrap_or(0);
+        if expires_at != 0 && expires_at <= now {
+            return Err(DriverTripRewardError::InvalidSuspension.into());
+        }
+
+        ctx.accounts.suspension_record.driver_pubkey = ctx.accounts.driver_account.driver_pubkey;
+        ctx.accounts.suspension_record.index = ctx.accounts.driver_account.suspension_count;
+        ctx.accounts.suspension_record.reason_code = reason_code;
+        ctx.accounts.suspension_record.suspended_at = now;
+        ctx.accounts.suspension_record.expires_at = expires_at;
+        ctx.accounts.suspension_record.suspended_by = ctx.accounts.admin_pubkey.key();
+        ctx.accounts.suspension_record.reactivated_at = 0;
+        ctx.accounts.suspension_record.reactivated_by = Pubkey::default();
+        ctx.accounts.suspension_record.bump = ctx.bumps.suspension_record;
+
+        ctx.accounts.driver_account.is_active = false;
+        ctx.accounts.driver_account.suspension_reason = reason_code;
+        ctx.accounts.driver_account.suspended_until = expires_at;
+        ctx.accounts.driver_account.suspension_count += 1;
+
+        msg!("Driver {} suspended with reason {}", ctx.accounts.driver_account.driver_pubkey, reason_code);
+        Ok(())
+    }
+
+    /// Lift a driver's most recent suspension
+    pub fn reactivate_driver(ctx: Context<ReactivateDriver>) -> Result<()> {
+        if ctx.accounts.driver_account.is_active {
+            return Err(DriverTripRewardError::DriverNotSuspended.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+
+        ctx.accounts.suspension_record.reactivated_at = now;
+        ctx.accounts.suspension_record.reactivated_by = ctx.accounts.admin_pubkey.key();
+
+        ctx.accounts.driver_account.is_active = true;
+        ctx.accounts.driver_account.suspension_reason = 0;
+        ctx.accounts.driver_account.suspended_until = 0;
+
+        msg!("Driver {} reactivated", ctx.accounts.driver_account.driver_pubkey);
+        Ok(())
+    }
+
+    /// Initialize the unsafe driving penalties and review threshold
+    pub fn initialize_safety_config(ctx: Context<InitializeSafetyConfig>, penalties: [u32; UNSAFE_DRIVING_CATEGORIES], review_threshold: u32) -> Result<()> {
+        ctx.accounts.safety_config.penalties = penalties;
+        ctx.accounts.safety_config.review_threshold = review_threshold;
+        ctx.accounts.safety_config.bump = ctx.bumps.safety_config;
+        ctx.accounts.safety_config.validate()
+    }
+
+    /// Update the unsafe driving penalties and review threshold
+    pub fn update_safety_config(ctx: Context<UpdateSafetyConfig>, penalties: [u32; UNSAFE_DRIVING_CATEGORIES], review_threshold: u32) -> Result<()> {
+        ctx.accounts.safety_config.penalties = penalties;
+        ctx.accounts.safety_config.review_threshold = review_threshold;
+        ctx.accounts.safety_config.validate()
+    }
+
+    /// Report unsafe driving on a co-signed counted trip as its passenger, penalizing the driver's score
+    pub fn report_unsafe_driving(ctx: Context<ReportUnsafeDriving>, trip_id: u64, category: u8, evidence_hash: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.passenger_pubkey != ctx.accounts.passenger.key() || ctx.accounts.passenger.key() == ctx.accounts.driver_pubkey.key() {
+            return Err(DriverTripRewardError::Unauthorized.into());
+        }
+        if !ctx.accounts.trip_account.passenger_signed {
+            return Err(DriverTripRewardError::PassengerSignatureRequired.into());
+        }
+        if ctx.accounts.trip_account.status != 1 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if category as usize >= UNSAFE_DRIVING_CATEGORIES || evidence_hash == [0u8; 32] {
+            return Err(DriverTripRewardError::InvalidReport.into());
+        }
+        if now > ctx.accounts.trip_account.end_time.saturating_add(TRIP_DISPUTE_WINDOW) {
+            return Err(DriverTripRewardError::ReportWindowClosed.into());
+        }
+
+        // Completed trips are never scored, so the penalty falls on the driver's total.
+        let penalty = ctx.accounts.safety_config.penalties[category as usize];
+        ctx.accounts.trip_account.score = ctx.accounts.trip_account.score.saturating_sub(penalty);
+        ctx.accounts.driver_account.apply_penalty(penalty as u64);
+
+        ctx.accounts.report.trip_account = ctx.accounts.trip_account.key();
+        ctx.accounts.report.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.report.passenger_pubkey = ctx.accounts.passenger.key();
+        ctx.accounts.report.category = category;
+        ctx.accounts.report.evidence_hash = evidence_hash;
+        ctx.accounts.report.penalty = penalty;
+        ctx.accounts.report.reported_at = now;
+        ctx.accounts.report.bump = ctx.bumps.report;
+        ctx.accounts.passenger_account.disputes_opened += 1;
+
+        ctx.accounts.driver_account.unsafe_reports += 1;
+        ctx.accounts.driver_account.reports_since_review += 1;
+        if !ctx.accounts.driver_account.under_review && ctx.accounts.driver_account.reports_since_review >= ctx.accounts.safety_config.review_threshold {
+            ctx.accounts.driver_account.under_review = true;
+            msg!("Driver {} placed under review after {} reports", ctx.accounts.driver_pubkey.key(), ctx.accounts.driver_account.reports_since_review);
+        }
+
+        msg!("Unsafe driving reported on trip {} with category {}, penalty {}", trip_id, category, penalty);
+        Ok(())
+    }
+
+    /// Clear a driver's review, resetting the reports counted towards the next one
+    pub fn clear_driver_review(ctx: Context<ClearDriverReview>) -> Result<()> {
+        ctx.accounts.driver_account.under_review = false;
+        ctx.accounts.driver_account.reports_since_review = 0;
+
+        msg!("Driver {} review cleared", ctx.accounts.driver_account.driver_pubkey);
+        Ok(())
+    }
+
+    /// Initialize the trip plausibility rules
+    pub fn initialize_trip_rules(ctx: Context<InitializeTripRules>, min_distance: u64, max_distance: u64, min_duration: u64, min_avg_speed: u64, max_avg_speed: u64) -> Result<()> {
+        ctx.accounts.trip_rules.min_distance = min_distance;
+        ctx.accounts.trip_rules.max_distance = max_distance;
+        ctx.accounts.trip_rules.min_duration = min_duration;
+        ctx.accounts.trip_rules.min_avg_speed = min_avg_speed;
+        ctx.accounts.trip_rules.max_avg_speed = max_avg_speed;
+        ctx.accounts.trip_rules.bump = ctx.bumps.trip_rules;
+        ctx.accounts.trip_rules.max_clock_drift = DEFAULT_MAX_CLOCK_DRIFT;
+        ctx.accounts.trip_rules.max_trip_age = DEFAULT_MAX_TRIP_AGE;
+        ctx.accounts.trip_rules.validate()
+    }
+
+    /// Update the trip plausibility rules
+    pub fn update_trip_rules(ctx: Context<UpdateTripRules>, min_distance: u64, max_distance: u64, min_duration: u64, min_avg_speed: u64, max_avg_speed: u64) -> Result<()> {
+        ctx.accounts.trip_rules.min_distance = min_distance;
+        ctx.accounts.trip_rules.max_distance = max_distance;
+        ctx.accounts.trip_rules.min_duration = min_duration;
+        ctx.accounts.trip_rules.min_avg_speed = min_avg_speed;
+        ctx.accounts.trip_rules.max_avg_speed = max_avg_speed;
+        ctx.accounts.trip_rules.validate()
+    }
+
+    /// Update how far client timestamps may deviate from the cluster clock
+    pub fn set_clock_tolerances(ctx: Context<UpdateTripRules>, max_clock_drift: i64, max_trip_age: i64) -> Result<()> {
+        ctx.accounts.trip_rules.max_clock_drift = max_clock_drift;
+        ctx.accounts.trip_rules.max_trip_age = max_trip_age;
+        ctx.accounts.trip_rules.validate()
+    }
+
+    /// Initialize the scoring config at version 1
+    pub fn initialize_scoring_config(ctx: Context<InitializeScoringConfig>, weights: ScoringWeights) -> Result<()> {
+        weights.validate()?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.scoring_config.version = 1;
+        ctx.accounts.scoring_config.weights = weights;
+        ctx.accounts.scoring_config.published_at = now;
+        ctx.accounts.scoring_config.bump = ctx.bumps.scoring_config;
+
+        ctx.accounts.scoring_version.version = 1;
+        ctx.accounts.scoring_version.weights = weights;
+        ctx.accounts.scoring_version.published_at = now;
+        ctx.accounts.scoring_version.bump = ctx.bumps.scoring_version;
+        Ok(())
+    }
+
+    /// Publish new scoring weights under the next version
+    pub fn publish_scoring_config(ctx: Context<PublishScoringConfig>, weights: ScoringWeights) -> Result<()> {
+        weights.validate()?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.scoring_config.version += 1;
+        ctx.accounts.scoring_config.weights = weights;
+        ctx.accounts.scoring_config.published_at = now;
+
+        ctx.accounts.scoring_version.version = ctx.accounts.scoring_config.version;
+        ctx.accounts.scoring_version.weights = weights;
+        ctx.accounts.scoring_version.published_at = now;
+        ctx.accounts.scoring_version.bump = ctx.bumps.scoring_version;
+
+        msg!("Published scoring config version {}", ctx.accounts.scoring_config.version);
+        Ok(())
+    }
+
+    /// Append a telemetry chunk hash to the trip's hash chain
+    pub fn commit_telemetry_chunk(ctx: Context<CommitTelemetryChunk>, trip_id: u64, chunk_index: u32, chunk_hash: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_COMMIT_TELEMETRY, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        if ctx.accounts.trip_account.status != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        if chunk_index != ctx.accounts.trip_account.telemetry_chunks {
+            return Err(DriverTripRewardError::InvalidChunkIndex.into());
+        }
+
+        ctx.accounts.trip_account.telemetry_chain =
+            TripAccount::next_telemetry_chain(&ctx.accounts.trip_account.telemetry_chain, chunk_index, &chunk_hash);
+        ctx.accounts.trip_account.telemetry_chunks += 1;
+        Ok(())
+    }
+
+    /// Record the distance driven since the trip's previous checkpoint
+    pub fn checkpoint_trip(ctx: Context<CheckpointTrip>, trip_id: u64, distance: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        authorize_driver(&ctx.accounts.driver_pubkey.key(), &ctx.accounts.authority.key(), &ctx.accounts.session, SESSION_SCOPE_CHECKPOINT_TRIP, now)?;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        // Submitted trips arrive with their totals and are never in progress.
+        if ctx.accounts.trip_account.status != 0 || ctx.accounts.trip_account.distance != 0 {
+            return Err(DriverTripRewardError::InvalidTripStatus.into());
+        }
+        let elapsed = now - ctx.accounts.trip_account.last_checkpoint_at;
+        if elapsed < MIN_CHECKPOINT_INTERVAL {
+            return Err(DriverTripRewardError::CheckpointTooSoon.into());
+        }
+        ctx.accounts.trip_rules.check_segment(distance, elapsed as u64)?;
+
+        ctx.accounts.trip_account.checkpoints += 1;
+        ctx.accounts.trip_account.checkpoint_distance += distance;
+        ctx.accounts.trip_account.checkpoint_duration += elapsed as u64;
+        ctx.accounts.trip_account.last_checkpoint_at = now;
+        msg!(
+            "Trip {} checkpoint {}: {} distance over {} seconds",
+            trip_id,
+            ctx.accounts.trip_account.checkpoints,
+            ctx.accounts.trip_account.checkpoint_distance,
+            ctx.accounts.trip_account.checkpoint_duration
+        );
+        Ok(())
+    }
+
+    /// Authorize an ephemeral session key to act for the driver
+    pub fn create_session(ctx: Context<CreateSession>, session_key: Pubkey, expires_at: i64, allowed_instructions: u32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.driver_account.is_suspended(now) {
+            return Err(DriverTripRewardError::DriverSuspended.into());
+        }
+        if session_key == ctx.accounts.driver_pubkey.key() {
+            return Err(DriverTripRewardError::InvalidSession.into());
+        }
+        if expires_at <= now || expires_at - now > MAX_SESSION_DURATION {
+            return Err(DriverTripRewardError::InvalidSession.into());
+        }
+        if allowed_instructions == 0 || allowed_instructions & !SESSION_SCOPE_ALL != 0 {
+            return Err(DriverTripRewardError::InvalidSession.into());
+        }
+
+        ctx.accounts.session.driver_pubkey = ctx.accounts.driver_pubkey.key();
+        ctx.accounts.session.session_key = session_key;
+        ctx.accounts.session.created_at = now;
+        ctx.accounts.session.expires_at = expires_at;
+        ctx.accounts.session.allowed_instructions = allowed_instructions;
+        ctx.accounts.session.bump = ctx.bumps.session;
+
+        msg!("Session {} created for driver {}", session_key, ctx.accounts.driver_pubkey.key());
+        Ok(())
+    }
+
+    /// Revoke a session key and close its account
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        msg!("Session {} revoked for driver {}", ctx.accounts.session.session_key, ctx.accounts.driver_pubkey.key());
+        Ok(())
+    }
+
+    /// Close a counted or cancelled trip, folding counted trips into the
+    /// driver's trip history and refunding its rent to whoever paid for it.
+    /// Anyone may close a trip.
+    pub fn close_trip(ctx: Context<CloseTrip>, trip_id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.trip_account.trip_id != trip_id {
+            return Err(DriverTripRewardError::TripNotFound.into());
+        }
+        // Cancelled trips have nothing left to settle and stay out of the history.
+        let cancelled = ctx.accounts.trip_account.status == 2;
+        if !cancelled {
+            // Completed trips count without ever being verified.
+            if ctx.accounts.trip_account.status != 1 {
+                return Err(DriverTripRewardError::TripNotSettled.into());
+            }
+            // Even a rewarded trip stays open until it can no longer be reported or rated.
+            if now < ctx.accounts.trip_account.end_time.saturating_add(TRIP_DISPUTE_WINDOW.max(PASSENGER_RATING_WINDOW)) {
+                return Err(DriverTripRewardError::TripNotSettled.into());
+            }
+
+            ctx.accounts.driver_account.trip_history =
+                DriverAccount::next_trip_history(&ctx.accounts.driver_account.trip_history, &ctx.accounts.trip_account);
+            ctx.accounts.driver_account.closed_trips += 1;
+        }
+
+        let rent = ctx.accounts.trip_account.to_account_info().lamports();
+        let rent_payer = ctx.accounts.trip_account.rent_payer;
+        if let Some(sponsor_treasury) = &mut ctx.accounts.sponsor_treasury {
+            if sponsor_treasury.key() != rent_payer {
+                return Err(DriverTripRewardError::InvalidRentPayer.into());
+            }
+            ctx.accounts.driver_account.sponsored_rent = ctx.accounts.driver_account.sponsored_rent.saturating_sub(rent);
+            sponsor_treasury.total_refunded += rent;
+            ctx.accounts.trip_account.close(sponsor_treasury.to_account_info())?;
+        } else if let Some(rent_payer_account) = &ctx.accounts.rent_payer {
+            // Sponsored rent goes back through `sponsor_treasury`, which frees
+            // the driver's budget; the treasury is the only program-owned payer.
+            if rent_payer_account.key() != rent_payer || rent_payer_account.owner == &crate::ID {
+                return Err(DriverTripRewardError::InvalidRentPayer.into());
+            }
+            ctx.accounts.trip_account.close(rent_payer_account.to_account_info())?;
+        } else {
+            return Err(DriverTripRewardError::InvalidRentPayer.into());
+        }
+
+        msg!("Closed trip {} for driver {}", trip_id, ctx.accounts.driver_account.driver_pubkey);
+        Ok(())
+    }
+
+    /// Initialize the rent sponsor treasury with a per-driver budget and the
+    /// relayer whose inits it reimburses
+    pub fn initialize_sponsor_treasury(ctx: Context<InitializeSponsorTreasury>, per_driver_budget: u64, relayer: Pubkey) -> Result<()> {
+        ctx.accounts.sponsor_treasury.per_driver_budget = per_driver_budget;
+        ctx.accounts.sponsor_treasury.relayer = relayer;
+        ctx.accounts.sponsor_treasury.total_sponsored = 0;
+        ctx.accounts.sponsor_treasury.total_refunded = 0;
+        ctx.accounts.sponsor_treasury.is_active = true;
+        ctx.accounts.sponsor_treasury.bump = ctx.bumps.sponsor_treasury;
+        Ok(())
+    }
+
+    /// Update the per-driver budget or relayer, or pause sponsorship
+    pub fn update_sponsor_treasury(ctx: Context<UpdateSponsorTreasury>, per_driver_budget: u64, relayer: Pubkey, is_active: bool) -> Result<()> {
+        ctx.accounts.sponsor_treasury.per_driver_budget = per_driver_budget;
+        ctx.accounts.sponsor_treasury.relayer = relayer;
+        ctx.accounts.sponsor_treasury.is_active = is_active;
+        Ok(())
+    }
+
+    /// Deposit lamports into the sponsor treasury
+    pub fn fund_sponsor_treasury(ctx: Context<FundSponsorTreasury>, amount: u64) -> Result<()> {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.sponsor_treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+
+        msg!("Sponsor treasury funded with {} lamports", amount);
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------
+// Accounts Structs (from src/instructions/)
+// -----------------------------------------------------------------
+#[derive(Accounts)]
+pub struct InitializeDriver<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        init,
+        space = 8 + 32 + 4 + 8 + 8 + 8 + 4 + 1 + 1 + 8 + 4 + 4 + 8 + 32 + 8 + 1 + 8 + 4 + 8 + 32 + 32 + 4 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4 + 4 + 1 + 8,
+        payer = fee_payer,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePassenger<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 32 + 4 + 4 + 4 + 4 + 4 + 1,
+        payer = fee_payer,
+        seeds = [
+            b"passenger",
+            passenger_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub passenger_account: Account<'info, PassengerAccount>,
+    pub passenger_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(passenger_pubkey: Pubkey, trip_id: u64)]
+pub struct SubmitTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        init,
+        space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 4 + 32 + 1 + 32 + 4 + 32 + 4 + 32 + 8 + 32 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 4,
+        payer = fee_payer,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    pub system_program: Program<'info, System>,
+    /// The passenger, co-signing so that they may rate or report the trip.
+    #[account(constraint = passenger.key() == passenger_pubkey @ DriverTripRewardError::Unauthorized)]
+    pub passenger: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64, end_time: i64, rating: f32, trip_hash: [u8; 32])]
+pub struct VerifyTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    #[account(
+        seeds = [b"scoring_config"],
+        bump = scoring_config.bump,
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1,
+        payer = fee_payer,
+        seeds = [
+            b"trip_hash",
+            trip_hash.as_ref(),
+        ],
+        bump,
+    )]
+    pub trip_hash_record: Account<'info, TripHashRecord>,
+    pub system_program: Program<'info, System>,
+    /// Pool whose validators may finalize the trip.
+    #[account(
+        seeds = [
+            b"reward_pool",
+            &reward_pool_account.pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+    )]
+    pub reward_pool_account: Option<Account<'info, RewardPoolAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct InitializeRewardPool<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8,
+        payer = fee_payer,
+        seeds = [
+            b"reward_pool".as_ref(),
+            &pool_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        init,
+        payer = fee_payer,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA authority for the vault
+    #[account(
+        seeds = [b"vault_authority", reward_pool_account.key().as_ref()],
+        bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    pub admin_pubkey: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeValidator<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 1020, // Note: You should recalculate this
+        payer = fee_payer,
+        seeds = [
+            b"validator",
+            validator_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub validator_account: Account<'info, ValidatorAccount>,
+    pub validator_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetValidatorActive<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"validator",
+            validator_account.validator_pubkey.as_ref(),
+        ],
+        bump = validator_account.bump,
+    )]
+    pub validator_account: Account<'info, ValidatorAccount>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessPrivateData<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"validator",
+            validator_pubkey.key().as_ref(),
+        ],
+        bump = validator_account.bump,
+    )]
+    pub validator_account: Account<'info, ValidatorAccount>,
+    pub validator_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+        constraint = !reward_pool_account.is_native @ DriverTripRewardError::InvalidRewardPoolKind,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    
+	#[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+	#[account(
+        mut,
+        constraint = vault.owner == vault_authority.key(),
+		constraint = vault.mint == reward_mint.key(),
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Any wallet the driver chooses to receive the reward, e.g. a hardware wallet
+    pub destination_owner: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = destination_owner,
+        associated_token::token_program = token_program,
+    )]
+    pub driver_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+    /// CHECK: This is safe because we derive it from seeds
+    #[account(
+        seeds = [b"vault_authority", reward_pool_account.key().as_ref()],
+        bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8,
+        payer = fee_payer,
+        seeds = [
+            b"driver_pool",
+            reward_pool_account.key().as_ref(),
+            driver_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub driver_pool_state: Account<'info, DriverPoolState>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"reward_split", driver_pubkey.key().as_ref()],
+        bump = reward_split.bump,
+    )]
+    pub reward_split: Option<Account<'info, RewardSplit>>,
+    /// Advisory receipt marking the trip as rewarded; see `RewardPoolAccount::check_claim`.
+    #[account(
+        mut,
+        constraint = trip_account.driver_pubkey == driver_pubkey.key() @ DriverTripRewardError::TripNotFound,
+    )]
+    pub trip_account: Option<Account<'info, TripAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct InitializeSolRewardPool<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8,
+        payer = fee_payer,
+        seeds = [
+            b"reward_pool".as_ref(),
+            &pool_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        init,
+        space = 8 + 32 + 1,
+        payer = fee_payer,
+        seeds = [b"sol_vault", reward_pool_account.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    pub admin_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct FundSolRewardPool<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+        constraint = reward_pool_account.is_native @ DriverTripRewardError::InvalidRewardPoolKind,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        mut,
+        seeds = [b"sol_vault", reward_pool_account.key().as_ref()],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct ClaimSolRewards<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+        constraint = reward_pool_account.is_native @ DriverTripRewardError::InvalidRewardPoolKind,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        mut,
+        seeds = [b"sol_vault", reward_pool_account.key().as_ref()],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+    /// CHECK: Any wallet the driver chooses to receive the reward; only credited
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8,
+        payer = fee_payer,
+        seeds = [
+            b"driver_pool",
+            reward_pool_account.key().as_ref(),
+            driver_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub driver_pool_state: Account<'info, DriverPoolState>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: Fee recipient, bound to `config.treasury`; only credited
+    #[account(
+        mut,
+        address = config.treasury @ DriverTripRewardError::InvalidTreasury,
+    )]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"reward_split", driver_pubkey.key().as_ref()],
+        bump = reward_split.bump,
+    )]
+    pub reward_split: Option<Account<'info, RewardSplit>>,
+    /// Advisory receipt marking the trip as rewarded; see `RewardPoolAccount::check_claim`.
+    #[account(
+        mut,
+        constraint = trip_account.driver_pubkey == driver_pubkey.key() @ DriverTripRewardError::TripNotFound,
+    )]
+    pub trip_account: Option<Account<'info, TripAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateValidatorWeights<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"validator",
+            validator_pubkey.key().as_ref(),
+        ],
+        bump = validator_account.bump,
+    )]
+    pub validator_account: Account<'info, ValidatorAccount>,
+    pub validator_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct CompleteTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    #[account(
+        seeds = [b"scoring_config"],
+        bump = scoring_config.bump,
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    #[account(mut)]
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            b"passenger",
+            passenger_account.passenger_pubkey.as_ref(),
+        ],
+        bump = passenger_account.bump,
+    )]
+    pub passenger_account: Option<Account<'info, PassengerAccount>>,
+    #[account(
+        mut,
+        seeds = [b"fare_escrow", trip_account.key().as_ref()],
+        bump = fare_escrow.bump,
+    )]
+    pub fare_escrow: Option<Account<'info, FareEscrow>>,
+    /// CHECK: the escrow's passenger, checked in `settle_fare`; refunded the escrow rent
+    #[account(mut)]
+    pub passenger: Option<UncheckedAccount<'info>>,
+    pub fare_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub fare_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives a token fare: the driver's token account for `fare_mint`.
+    #[account(mut)]
+    pub fare_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(passenger_pubkey: Pubkey, trip_id: u64)]
+pub struct CreateTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        init,
+        space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 4 + 32 + 1 + 32 + 4 + 32 + 4 + 32 + 8 + 32 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 4,
+        payer = fee_payer,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    pub system_program: Program<'info, System>,
+    /// The passenger, co-signing so that they may rate or report the trip.
+    #[account(constraint = passenger.key() == passenger_pubkey @ DriverTripRewardError::Unauthorized)]
+    pub passenger: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct CreateTripWithFare<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 4 + 32 + 1 + 32 + 4 + 32 + 4 + 32 + 8 + 32 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 4,
+        payer = fee_payer,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    #[account(mut)]
+    pub passenger: Signer<'info>,
+    /// Must exist so that completing or reclaiming the fare can be recorded on it.
+    #[account(
+        seeds = [
+            b"passenger",
+            passenger.key().as_ref(),
+        ],
+        bump = passenger_account.bump,
+    )]
+    pub passenger_account: Account<'info, PassengerAccount>,
+    #[account(
+        init,
+        space = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 1,
+        payer = passenger,
+        seeds = [b"fare_escrow", trip_account.key().as_ref()],
+        bump,
+    )]
+    pub fare_escrow: Account<'info, FareEscrow>,
+    #[account(mint::token_program = token_program)]
+    pub fare_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = fare_mint,
+        token::authority = passenger,
+        token::token_program = token_program,
+    )]
+    pub passenger_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = passenger,
+        associated_token::mint = fare_mint,
+        associated_token::authority = fare_escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub fare_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct AddTripLeg<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+        realloc = trip_account.to_account_info().data_len() + TRIP_LEG_SPACE,
+        realloc::payer = fee_payer,
+        realloc::zero = false,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct RateTrip<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    /// CHECK: the driver the trip belongs to
+    pub driver_pubkey: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"passenger",
+            passenger.key().as_ref(),
+        ],
+        bump = passenger_account.bump,
+    )]
+    pub passenger_account: Account<'info, PassengerAccount>,
+    pub passenger: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct CancelTrip<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to
+    pub driver_pubkey: UncheckedAccount<'info>,
+    /// The driver, one of its session keys, or the escrow's passenger after the timeout
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            b"passenger",
+            passenger_account.passenger_pubkey.as_ref(),
+        ],
+        bump = passenger_account.bump,
+    )]
+    pub passenger_account: Option<Account<'info, PassengerAccount>>,
+    #[account(
+        mut,
+        seeds = [b"fare_escrow", trip_account.key().as_ref()],
+        bump = fare_escrow.bump,
+    )]
+    pub fare_escrow: Option<Account<'info, FareEscrow>>,
+    /// CHECK: the escrow's passenger, checked in `settle_fare`; refunded the escrow rent
+    #[account(mut)]
+    pub passenger: Option<UncheckedAccount<'info>>,
+    pub fare_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub fare_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives a token fare: the passenger's token account for `fare_mint`.
+    #[account(mut)]
+    pub fare_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitNextTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    #[account(
+        init,
+        space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 4 + 32 + 1 + 32 + 4 + 32 + 4 + 32 + 8 + 32 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 4,
+        payer = fee_payer,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &driver_account.next_trip_id()?.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNextTrip<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    #[account(
+        init,
+        space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 4 + 32 + 1 + 32 + 4 + 32 + 4 + 32 + 8 + 32 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 4,
+        payer = fee_payer,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &driver_account.next_trip_id()?.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct DistributeReward<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 32 + 1 + 2 + 32,
+        payer = fee_payer,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    pub admin_pubkey: Signer<'info>,
+    /// Only the program's upgrade authority can claim the admin role.
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin_pubkey.key()) @ DriverTripRewardError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardSplit<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 4 + MAX_SPLIT_BENEFICIARIES * (32 + 2 + 8) + 8 + 1,
+        payer = fee_payer,
+        seeds = [b"reward_split", driver_pubkey.key().as_ref()],
+        bump,
+    )]
+    pub reward_split: Account<'info, RewardSplit>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SuspendDriver<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_account.driver_pubkey.as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        init,
+        space = 8 + 32 + 4 + 1 + 8 + 8 + 32 + 8 + 32 + 1,
+        payer = fee_payer,
+        seeds = [
+            b"suspension",
+            driver_account.driver_pubkey.as_ref(),
+            &driver_account.suspension_count.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub suspension_record: Account<'info, SuspensionRecord>,
+    pub admin_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReactivateDriver<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_account.driver_pubkey.as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"suspension",
+            driver_account.driver_pubkey.as_ref(),
+            &driver_account.suspension_count.saturating_sub(1).to_le_bytes(),
+        ],
+        bump = suspension_record.bump,
+    )]
+    pub suspension_record: Account<'info, SuspensionRecord>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTripRules<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        space = 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8,
+        payer = fee_payer,
+        seeds = [b"trip_rules"],
+        bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    pub admin_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTripRules<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSafetyConfig<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        space = 8 + 4 * UNSAFE_DRIVING_CATEGORIES + 4 + 1,
+        payer = fee_payer,
+        seeds = [b"safety_config"],
+        bump,
+    )]
+    pub safety_config: Account<'info, SafetyConfig>,
+    pub admin_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSafetyConfig<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"safety_config"],
+        bump = safety_config.bump,
+    )]
+    pub safety_config: Account<'info, SafetyConfig>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct ReportUnsafeDriving<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            driver_pubkey.key().as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    #[account(
+        seeds = [b"safety_config"],
+        bump = safety_config.bump,
+    )]
+    pub safety_config: Account<'info, SafetyConfig>,
+    #[account(
+        init,
+        space = 8 + 32 + 32 + 32 + 1 + 32 + 4 + 8 + 1,
+        payer = passenger,
+        seeds = [b"unsafe_report", trip_account.key().as_ref()],
+        bump,
+    )]
+    pub report: Account<'info, UnsafeDrivingReport>,
+    /// CHECK: the driver the trip belongs to
+    pub driver_pubkey: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"passenger",
+            passenger.key().as_ref(),
+        ],
+        bump = passenger_account.bump,
+    )]
+    pub passenger_account: Account<'info, PassengerAccount>,
+    #[account(mut)]
+    pub passenger: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearDriverReview<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"driver",
+            driver_account.driver_pubkey.as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    pub admin_pubkey: Signer<'info>,
 }
 
-// -----------------------------------------------------------------
-// Accounts Structs (from src/instructions/)
-// -----------------------------------------------------------------
 #[derive(Accounts)]
-pub struct InitializeDriver<'info> {
+pub struct InitializeScoringConfig<'info> {
     #[account(mut)]
     pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        space = 8 + 4 + ScoringWeights::SIZE + 8 + 1,
+        payer = fee_payer,
+        seeds = [b"scoring_config"],
+        bump,
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
     #[account(
         init,
-        space = 120, // Note: You should recalculate this space
+        space = 8 + 4 + ScoringWeights::SIZE + 8 + 1,
         payer = fee_payer,
         seeds = [
-            b"driver",
-            driver_pubkey.key().as_ref(),
+            b"scoring_version".as_ref(),
+            &1u32.to_le_bytes(),
         ],
         bump,
     )]
-    pub driver_account: Account<'info, DriverAccount>,
-    pub driver_pubkey: Signer<'info>,
+    pub scoring_version: Account<'info, ScoringVersion>,
+    pub admin_pubkey: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(trip_id: u64)]
-pub struct SubmitTrip<'info> {
+pub struct PublishScoringConfig<'info> {
     #[account(mut)]
     pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"scoring_config"],
+        bump = scoring_config.bump,
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
     #[account(
         init,
-        space = 130, // Note: You should recalculate this space
+        space = 8 + 4 + ScoringWeights::SIZE + 8 + 1,
         payer = fee_payer,
+        seeds = [
+            b"scoring_version".as_ref(),
+            &(scoring_config.version + 1).to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub scoring_version: Account<'info, ScoringVersion>,
+    pub admin_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trip_id: u64)]
+pub struct CommitTelemetryChunk<'info> {
+    #[account(
+        mut,
         seeds = [
             b"trip",
             driver_pubkey.key().as_ref(),
             &trip_id.to_le_bytes(),
         ],
-        bump,
+        bump = trip_account.bump,
     )]
     pub trip_account: Account<'info, TripAccount>,
-    pub driver_pubkey: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
+    )]
+    pub driver_account: Account<'info, DriverAccount>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
 }
 
 #[derive(Accounts)]
 #[instruction(trip_id: u64)]
-pub struct VerifyTrip<'info> {
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+pub struct CheckpointTrip<'info> {
     #[account(
         mut,
         seeds = [
@@ -517,7 +4002,6 @@ pub struct VerifyTrip<'info> {
     )]
     pub trip_account: Account<'info, TripAccount>,
     #[account(
-        mut,
         seeds = [
             b"driver",
             driver_pubkey.key().as_ref(),
@@ -525,72 +4009,138 @@ pub struct VerifyTrip<'info> {
         bump = driver_account.bump,
     )]
     pub driver_account: Account<'info, DriverAccount>,
-    pub driver_pubkey: Signer<'info>,
+    #[account(
+        seeds = [b"trip_rules"],
+        bump = trip_rules.bump,
+    )]
+    pub trip_rules: Account<'info, TripRules>,
+    /// CHECK: the driver the trip belongs to; `authority` must be this key or one of its session keys
+    pub driver_pubkey: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
 }
 
 #[derive(Accounts)]
-#[instruction(pool_id: u64)]
-pub struct InitializeRewardPool<'info> {
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
     #[account(mut)]
     pub fee_payer: Signer<'info>,
     #[account(
         init,
-        space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 8, // Note: You should recalculate this
+        space = 8 + 32 + 32 + 8 + 8 + 4 + 1,
         payer = fee_payer,
         seeds = [
-            b"reward_pool",
-            //&pool_id.to_le_bytes(),
+            b"session",
+            driver_pubkey.key().as_ref(),
+            session_key.as_ref(),
         ],
         bump,
     )]
-    pub reward_pool_account: Account<'info, RewardPoolAccount>,
-    #[account(
-        init,
-        payer = fee_payer,
-        token::mint = reward_mint,
-        token::authority = vault_authority,
-        token::token_program = token_program,
-    )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
-    /// CHECK: PDA authority for the vault
+    pub session: Account<'info, SessionAccount>,
     #[account(
-        seeds = [b"vault_authority", reward_pool_account.key().as_ref()],
-        bump,
+        seeds = [
+            b"driver",
+            driver_pubkey.key().as_ref(),
+        ],
+        bump = driver_account.bump,
     )]
-    pub vault_authority: UncheckedAccount<'info>,
+    pub driver_account: Account<'info, DriverAccount>,
+    pub driver_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
     #[account(
-        mint::token_program = token_program
+        mut,
+        close = driver_pubkey,
+        seeds = [
+            b"session",
+            driver_pubkey.key().as_ref(),
+            session.session_key.as_ref(),
+        ],
+        bump = session.bump,
     )]
-    pub reward_mint: InterfaceAccount<'info, Mint>,
-    pub admin_pubkey: Signer<'info>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    pub session: Account<'info, SessionAccount>,
+    #[account(mut)]
+    pub driver_pubkey: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeValidator<'info> {
+pub struct InitializeSponsorTreasury<'info> {
     #[account(mut)]
     pub fee_payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
     #[account(
         init,
-        space = 1020, // Note: You should recalculate this
+        space = 8 + 8 + 32 + 8 + 8 + 1 + 1,
         payer = fee_payer,
-        seeds = [
-            b"validator",
-            validator_pubkey.key().as_ref(),
-        ],
+        seeds = [b"sponsor_treasury"],
         bump,
     )]
-    pub validator_account: Account<'info, ValidatorAccount>,
-    pub validator_pubkey: Signer<'info>,
+    pub sponsor_treasury: Account<'info, SponsorTreasury>,
+    pub admin_pubkey: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ProcessPrivateData<'info> {
+#[instruction(pool_id: u64)]
+pub struct UpdateRewardPool<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    pub admin_pubkey: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64, trip_id: u64)]
+pub struct FinalizeTrip<'info> {
     #[account(mut)]
     pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"trip",
+            trip_account.driver_pubkey.as_ref(),
+            &trip_id.to_le_bytes(),
+        ],
+        bump = trip_account.bump,
+    )]
+    pub trip_account: Account<'info, TripAccount>,
     #[account(
         mut,
         seeds = [
@@ -600,52 +4150,60 @@ pub struct ProcessPrivateData<'info> {
         bump = validator_account.bump,
     )]
     pub validator_account: Account<'info, ValidatorAccount>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1,
+        payer = fee_payer,
+        seeds = [
+            b"validator_fees",
+            reward_pool_account.key().as_ref(),
+            validator_pubkey.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub validator_fee_balance: Account<'info, ValidatorFeeBalance>,
     pub validator_pubkey: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(pool_id: u64)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+pub struct ClaimValidatorFees<'info> {
     #[account(
-        mut,
         seeds = [
             b"reward_pool",
             &pool_id.to_le_bytes(),
         ],
         bump = reward_pool_account.bump,
+        constraint = !reward_pool_account.is_native @ DriverTripRewardError::InvalidRewardPoolKind,
     )]
     pub reward_pool_account: Account<'info, RewardPoolAccount>,
-    
-	#[account(mint::token_program = token_program)]
+    #[account(mint::token_program = token_program)]
     pub reward_mint: InterfaceAccount<'info, Mint>,
-
-	#[account(
+    #[account(
         mut,
-        constraint = vault.owner == vault_authority.key(),
-		constraint = vault.mint == reward_mint.key(),
+        address = reward_pool_account.vault,
+        constraint = vault.mint == reward_mint.key(),
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
-
     #[account(
         mut,
-        constraint = driver_token_account.owner == driver_pubkey.key(),
-		constraint = driver_token_account.mint == reward_mint.key(),
+        constraint = validator_token_account.owner == validator_pubkey.key(),
+        constraint = validator_token_account.mint == reward_mint.key(),
     )]
-    pub driver_token_account: InterfaceAccount<'info, TokenAccount>,
-
+    pub validator_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         seeds = [
-            b"driver",
-            driver_pubkey.key().as_ref(),
+            b"validator_fees",
+            reward_pool_account.key().as_ref(),
+            validator_pubkey.key().as_ref(),
         ],
-        bump = driver_account.bump,
+        bump = validator_fee_balance.bump,
     )]
-    pub driver_account: Account<'info, DriverAccount>,
-    pub driver_pubkey: Signer<'info>,
-    /// CHECK: This is safe because we derive it from seeds
+    pub validator_fee_balance: Account<'info, ValidatorFeeBalance>,
+    pub validator_pubkey: Signer<'info>,
+    /// CHECK: PDA signing for the vault
     #[account(
         seeds = [b"vault_authority", reward_pool_account.key().as_ref()],
         bump,
@@ -655,91 +4213,96 @@ pub struct ClaimRewards<'info> {
 }
 
 #[derive(Accounts)]
-pub struct UpdateValidatorWeights<'info> {
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+#[instruction(pool_id: u64)]
+pub struct ClaimSolValidatorFees<'info> {
+    #[account(
+        seeds = [
+            b"reward_pool",
+            &pool_id.to_le_bytes(),
+        ],
+        bump = reward_pool_account.bump,
+        constraint = reward_pool_account.is_native @ DriverTripRewardError::InvalidRewardPoolKind,
+    )]
+    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    #[account(
+        mut,
+        seeds = [b"sol_vault", reward_pool_account.key().as_ref()],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
     #[account(
         mut,
         seeds = [
-            b"validator",
+            b"validator_fees",
+            reward_pool_account.key().as_ref(),
             validator_pubkey.key().as_ref(),
         ],
-        bump = validator_account.bump,
+        bump = validator_fee_balance.bump,
     )]
-    pub validator_account: Account<'info, ValidatorAccount>,
+    pub validator_fee_balance: Account<'info, ValidatorFeeBalance>,
+    #[account(mut)]
     pub validator_pubkey: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(trip_id: u64)]
-pub struct CompleteTrip<'info> {
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+pub struct UpdateSponsorTreasury<'info> {
     #[account(
-        mut,
-        seeds = [
-            b"trip",
-            driver_pubkey.key().as_ref(),
-            &trip_id.to_le_bytes(),
-        ],
-        bump = trip_account.bump,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_pubkey == admin_pubkey.key() @ DriverTripRewardError::Unauthorized,
     )]
-    pub trip_account: Account<'info, TripAccount>,
+    pub config: Account<'info, ProgramConfig>,
     #[account(
         mut,
-        seeds = [
-            b"driver",
-            driver_pubkey.key().as_ref(),
-        ],
-        bump = driver_account.bump,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
     )]
-    pub driver_account: Account<'info, DriverAccount>,
-    pub driver_pubkey: Signer<'info>,
+    pub sponsor_treasury: Account<'info, SponsorTreasury>,
+    pub admin_pubkey: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(trip_id: u64)]
-pub struct CreateTrip<'info> {
+pub struct FundSponsorTreasury<'info> {
     #[account(mut)]
-    pub fee_payer: Signer<'info>,
+    pub funder: Signer<'info>,
     #[account(
-        init,
-        space = 130, // Note: You should recalculate this
-        payer = fee_payer,
-        seeds = [
-            b"trip",
-            driver_pubkey.key().as_ref(),
-            &trip_id.to_le_bytes(),
-        ],
-        bump,
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
     )]
-    pub trip_account: Account<'info, TripAccount>,
-    pub driver_pubkey: Signer<'info>,
+    pub sponsor_treasury: Account<'info, SponsorTreasury>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(pool_id: u64)]
-pub struct DistributeReward<'info> {
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+#[instruction(trip_id: u64)]
+pub struct CloseTrip<'info> {
     #[account(
         mut,
         seeds = [
-            b"reward_pool",
-            &pool_id.to_le_bytes(),
+            b"trip",
+            trip_account.driver_pubkey.as_ref(),
+            &trip_id.to_le_bytes(),
         ],
-        bump = reward_pool_account.bump,
+        bump = trip_account.bump,
     )]
-    pub reward_pool_account: Account<'info, RewardPoolAccount>,
+    pub trip_account: Account<'info, TripAccount>,
     #[account(
         mut,
         seeds = [
             b"driver",
-            driver_pubkey.key().as_ref(),
+            trip_account.driver_pubkey.as_ref(),
         ],
         bump = driver_account.bump,
     )]
     pub driver_account: Account<'info, DriverAccount>,
-    pub driver_pubkey: Signer<'info>,
-}
\ No newline at end of file
+    #[account(
+        mut,
+        seeds = [b"sponsor_treasury"],
+        bump = sponsor_treasury.bump,
+    )]
+    pub sponsor_treasury: Option<Account<'info, SponsorTreasury>>,
+    /// CHECK: receives the rent of an unsponsored trip; must match `trip_account.rent_payer`
+    #[account(mut)]
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+}
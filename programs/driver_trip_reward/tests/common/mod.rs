@@ -0,0 +1,1494 @@
+#![allow(deprecated)]
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id, spl_associated_token_account,
+};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee, ExtensionType, StateWithExtensions},
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+use driver_trip_reward::{DriverTripRewardError, ScoringWeights, SplitShare, TripAccount, NONCE_TRIP_ID_BASE};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const MINT_DECIMALS: u8 = 6;
+
+/// Start time of the trips the tests record; `TestEnv::new` sets the cluster
+/// clock to it so trip timestamps are within the default clock tolerances.
+pub const START_TIME: i64 = 1_700_000_000;
+
+/// Permissive trip rules installed by `TestEnv::new`:
+/// (min_distance, max_distance, min_duration, min_avg_speed, max_avg_speed).
+pub const DEFAULT_TRIP_RULES: (u64, u64, u64, u64, u64) = (1, 1_000, 60, 1, 200);
+
+/// The weights `verify_trip` used before scoring became configurable.
+pub const DEFAULT_SCORING_WEIGHTS: ScoringWeights = ScoringWeights {
+    rating_weight: 20,
+    distance_threshold: 1,
+    distance_bonus: 50,
+    min_bonus_duration: 60,
+    max_bonus_duration: 3_600,
+    duration_bonus: 30,
+    fare_threshold: 100_000_000,
+    fare_bonus: 20,
+    max_score: 100,
+};
+
+// Anchor's entrypoint ties the account slice to the lifetime of the account
+// infos, which the native processor shim cannot express.
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    driver_trip_reward::entry(program_id, accounts, data)
+}
+
+pub struct TestEnv {
+    pub ctx: ProgramTestContext,
+    pub admin: Keypair,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        let mut env = Self::start().await;
+        let payer = env.payer().pubkey();
+        let admin = env.admin.insecure_clone();
+        let (min_distance, max_distance, min_duration, min_avg_speed, max_avg_speed) = DEFAULT_TRIP_RULES;
+        env.send(
+            &[
+                initialize_config_ix(&payer, &admin.pubkey()),
+                initialize_trip_rules_ix(
+                    &payer,
+                    &admin.pubkey(),
+                    min_distance,
+                    max_distance,
+                    min_duration,
+                    min_avg_speed,
+                    max_avg_speed,
+                ),
+                initialize_scoring_config_ix(&payer, &admin.pubkey(), DEFAULT_SCORING_WEIGHTS),
+            ],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+        env.set_unix_timestamp(START_TIME).await;
+        env
+    }
+
+    /// Starts the program with `admin` as its upgrade authority but no config.
+    pub async fn start() -> Self {
+        let mut program_test = ProgramTest::new(
+            "driver_trip_reward",
+            driver_trip_reward::ID,
+            processor!(process_instruction),
+        );
+        // The native processor has no upgradeable loader accounts; stand in the
+        // ProgramData account that names the admin as upgrade authority.
+        let admin = Keypair::new();
+        let program_data = UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(admin.pubkey()),
+        };
+        program_test.add_account(
+            program_data_pda(),
+            Account::new_data(1_000_000_000, &program_data, &bpf_loader_upgradeable::ID).unwrap(),
+        );
+        Self {
+            ctx: program_test.start_with_context().await,
+            admin,
+        }
+    }
+
+    pub fn payer(&self) -> Keypair {
+        self.ctx.payer.insecure_clone()
+    }
+
+    pub async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        self.ctx.last_blockhash = blockhash;
+        let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    /// Like `send`, but also returns the transaction's log messages.
+    pub async fn send_with_logs(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> (Result<(), BanksClientError>, Vec<String>) {
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        self.ctx.last_blockhash = blockhash;
+        let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+        let outcome = self.ctx.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+        let logs = outcome.metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+        (outcome.result.map_err(BanksClientError::from), logs)
+    }
+
+    /// Waits for a fresh blockhash so an identical transaction is not deduplicated.
+    pub async fn refresh_blockhash(&mut self) {
+        self.ctx.get_new_latest_blockhash().await.unwrap();
+    }
+
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.ctx.banks_client.get_account(address).await.unwrap().expect("account not found");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.ctx.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    pub async fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    pub async fn set_epoch(&mut self, epoch: u64) {
+        let mut clock = self.clock().await;
+        clock.epoch = epoch;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    pub async fn account_exists(&mut self, address: Pubkey) -> bool {
+        self.ctx.banks_client.get_account(address).await.unwrap().is_some()
+    }
+
+    pub async fn account_size(&mut self, address: Pubkey) -> usize {
+        self.ctx.banks_client.get_account(address).await.unwrap().expect("account not found").data.len()
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.ctx.banks_client.get_balance(address).await.unwrap()
+    }
+
+    pub async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(address).await.unwrap().expect("token account not found");
+        StateWithExtensions::<TokenAccountState>::unpack(&account.data).unwrap().base.amount
+    }
+
+    pub async fn funded_keypair(&mut self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        let payer = self.payer().pubkey();
+        self.send(&[system_instruction::transfer(&payer, &keypair.pubkey(), lamports)], &[])
+            .await
+            .unwrap();
+        keypair
+    }
+
+    /// Creates a Token-2022 mint whose authority is the test payer.
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        self.create_mint_with_extensions(&mint, &[], &[]).await;
+        mint.pubkey()
+    }
+
+    /// Creates a Token-2022 mint with `extensions`, which `init_extensions`
+    /// initialize before the mint itself.
+    pub async fn create_mint_with_extensions(&mut self, mint: &Keypair, extensions: &[ExtensionType], init_extensions: &[Instruction]) {
+        let payer = self.payer().pubkey();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let space = ExtensionType::try_calculate_account_len::<MintState>(extensions).unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::ID,
+        )];
+        instructions.extend_from_slice(init_extensions);
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::ID, &mint.pubkey(), &payer, None, MINT_DECIMALS)
+                .unwrap(),
+        );
+        self.send(&instructions, &[mint]).await.unwrap();
+    }
+
+    /// Creates a Token-2022 mint charging `transfer_fee_basis_points` on
+    /// transfers, capped at `maximum_fee`.
+    pub async fn create_fee_mint(&mut self, transfer_fee_basis_points: u16, maximum_fee: u64) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer().pubkey();
+        let init_fee = transfer_fee::instruction::initialize_transfer_fee_config(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            Some(&payer),
+            Some(&payer),
+            transfer_fee_basis_points,
+            maximum_fee,
+        )
+        .unwrap();
+        self.create_mint_with_extensions(&mint, &[ExtensionType::TransferFeeConfig], &[init_fee]).await;
+        mint.pubkey()
+    }
+
+    /// Creates a mint owned by the original SPL Token program.
+    pub async fn create_spl_token_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer().pubkey();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, None, MINT_DECIMALS).unwrap(),
+        ];
+        self.send(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Mints to `destination` under whichever token program owns `mint`.
+    pub async fn mint_to(&mut self, mint: Pubkey, destination: Pubkey, amount: u64) {
+        let payer = self.payer().pubkey();
+        let token_program = self.owner(mint).await;
+        let ix = spl_token_2022::instruction::mint_to(&token_program, &mint, &destination, &payer, &[], amount).unwrap();
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// Creates the associated token account of `owner` under whichever token
+    /// program owns `mint`.
+    pub async fn create_ata(&mut self, owner: Pubkey, mint: Pubkey) -> Pubkey {
+        let payer = self.payer().pubkey();
+        let token_program = self.owner(mint).await;
+        let ix = spl_associated_token_account::instruction::create_associated_token_account(
+            &payer,
+            &owner,
+            &mint,
+            &token_program,
+        );
+        self.send(&[ix], &[]).await.unwrap();
+        get_associated_token_address_with_program_id(&owner, &mint, &token_program)
+    }
+
+    pub async fn owner(&mut self, address: Pubkey) -> Pubkey {
+        self.ctx.banks_client.get_account(address).await.unwrap().expect("account not found").owner
+    }
+}
+
+/// Token-2022 associated token account of `owner` for `mint`.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::ID)
+}
+
+pub fn driver_pda(driver: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"driver", driver.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn trip_pda(driver: &Pubkey, trip_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"trip", driver.as_ref(), &trip_id.to_le_bytes()], &driver_trip_reward::ID).0
+}
+
+pub fn reward_pool_pda(pool_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward_pool", &pool_id.to_le_bytes()], &driver_trip_reward::ID).0
+}
+
+pub fn vault_authority_pda(reward_pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault_authority", reward_pool.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &driver_trip_reward::ID).0
+}
+
+pub fn program_data_pda() -> Pubkey {
+    Pubkey::find_program_address(&[driver_trip_reward::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+pub fn trip_rules_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"trip_rules"], &driver_trip_reward::ID).0
+}
+
+pub fn scoring_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"scoring_config"], &driver_trip_reward::ID).0
+}
+
+pub fn scoring_version_pda(version: u32) -> Pubkey {
+    Pubkey::find_program_address(&[b"scoring_version", &version.to_le_bytes()], &driver_trip_reward::ID).0
+}
+
+pub fn session_pda(driver: &Pubkey, session_key: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"session", driver.as_ref(), session_key.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn suspension_pda(driver: &Pubkey, index: u32) -> Pubkey {
+    Pubkey::find_program_address(&[b"suspension", driver.as_ref(), &index.to_le_bytes()], &driver_trip_reward::ID).0
+}
+
+pub fn sponsor_treasury_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"sponsor_treasury"], &driver_trip_reward::ID).0
+}
+
+pub fn reward_split_pda(driver: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward_split", driver.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn sol_vault_pda(reward_pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"sol_vault", reward_pool.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn driver_pool_pda(reward_pool: &Pubkey, driver: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"driver_pool", reward_pool.as_ref(), driver.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn safety_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"safety_config"], &driver_trip_reward::ID).0
+}
+
+pub fn unsafe_report_pda(trip_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"unsafe_report", trip_account.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn passenger_pda(passenger: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"passenger", passenger.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn fare_escrow_pda(trip_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fare_escrow", trip_account.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn trip_hash_record_pda(trip_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"trip_hash", trip_hash.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn validator_pda(validator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"validator", validator.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn validator_fees_pda(reward_pool: &Pubkey, validator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"validator_fees", reward_pool.as_ref(), validator.as_ref()], &driver_trip_reward::ID).0
+}
+
+pub fn program_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: driver_trip_reward::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Head of the telemetry hash chain after committing `chunks` in order.
+pub fn telemetry_chain(chunks: &[[u8; 32]]) -> [u8; 32] {
+    chunks
+        .iter()
+        .enumerate()
+        .fold([0u8; 32], |chain, (index, chunk)| TripAccount::next_telemetry_chain(&chain, index as u32, chunk))
+}
+
+/// Re-signs a driver instruction with `session_key` in place of the driver. The
+/// optional `session` account directly follows the `authority` signer.
+pub fn with_session(mut ix: Instruction, driver: &Pubkey, session_key: &Pubkey) -> Instruction {
+    let authority = ix
+        .accounts
+        .iter()
+        .position(|meta| meta.is_signer && meta.pubkey == *driver)
+        .expect("instruction has no driver signer");
+    ix.accounts[authority].pubkey = *session_key;
+    assert_eq!(ix.accounts[authority + 1].pubkey, driver_trip_reward::ID);
+    ix.accounts[authority + 1].pubkey = session_pda(driver, session_key);
+    ix
+}
+
+/// Retargets a reward pool instruction built for Token-2022 to `token_program`.
+pub fn with_token_program(mut ix: Instruction, token_program: &Pubkey) -> Instruction {
+    for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == spl_token_2022::ID) {
+        meta.pubkey = *token_program;
+    }
+    ix
+}
+
+/// Has the sponsor treasury reimburse the rent of `ix`. The optional
+/// `sponsor_treasury` account directly follows `fee_payer`.
+pub fn with_sponsor(mut ix: Instruction) -> Instruction {
+    assert_eq!(ix.accounts[1].pubkey, driver_trip_reward::ID);
+    ix.accounts[1] = AccountMeta::new(sponsor_treasury_pda(), false);
+    ix
+}
+
+/// Asserts that the program logged `message`. When the program runs natively
+/// instead of as SBF, `msg!` writes to stdout rather than the transaction log,
+/// so there is nothing to check.
+pub fn assert_program_log(logs: &[String], message: &str) {
+    if !logs.iter().any(|log| log.starts_with("Program log: ")) {
+        return;
+    }
+    let expected = format!("Program log: {message}");
+    assert!(logs.contains(&expected), "{expected:?} not in {logs:?}");
+}
+
+pub fn assert_program_error(result: Result<(), BanksClientError>, expected: DriverTripRewardError) {
+    let code: u32 = expected.into();
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+pub fn assert_anchor_error(result: Result<(), BanksClientError>, expected: anchor_lang::error::ErrorCode) {
+    let code: u32 = expected.into();
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected anchor error {code}, got {other:?}"),
+    }
+}
+
+// -----------------------------------------------------------------
+// Instruction builders
+// -----------------------------------------------------------------
+pub fn initialize_driver_ix(payer: &Pubkey, driver: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeDriver {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeDriver {},
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn submit_trip_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    passenger: Pubkey,
+    trip_id: u64,
+    start_time: i64,
+    distance: u64,
+    duration: u64,
+    fare: u64,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SubmitTrip {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            system_program: system_program::ID,
+            passenger: None,
+        },
+        driver_trip_reward::instruction::SubmitTrip {
+            passenger_pubkey: passenger,
+            trip_id,
+            start_time,
+            distance,
+            duration,
+            fare,
+        },
+    )
+}
+
+pub fn create_trip_ix(payer: &Pubkey, driver: &Pubkey, passenger: Pubkey, trip_id: u64, start_time: i64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CreateTrip {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            system_program: system_program::ID,
+            passenger: None,
+        },
+        driver_trip_reward::instruction::CreateTrip {
+            passenger_pubkey: passenger,
+            trip_id,
+            start_time,
+        },
+    )
+}
+
+/// `create_trip` with `passenger` escrowing a lamport fare.
+pub fn create_trip_with_fare_ix(payer: &Pubkey, driver: &Pubkey, passenger: &Pubkey, trip_id: u64, start_time: i64, fare: u64) -> Instruction {
+    create_trip_with_fare_for_ix(payer, driver, passenger, trip_id, start_time, fare, None)
+}
+
+/// `create_trip` with `passenger` escrowing a Token-2022 fare of `mint` from
+/// `passenger_token_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_trip_with_token_fare_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    passenger: &Pubkey,
+    trip_id: u64,
+    start_time: i64,
+    fare: u64,
+    mint: &Pubkey,
+    passenger_token_account: &Pubkey,
+) -> Instruction {
+    create_trip_with_fare_for_ix(payer, driver, passenger, trip_id, start_time, fare, Some((mint, passenger_token_account)))
+}
+
+fn create_trip_with_fare_for_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    passenger: &Pubkey,
+    trip_id: u64,
+    start_time: i64,
+    fare: u64,
+    token: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let trip_account = trip_pda(driver, trip_id);
+    let fare_escrow = fare_escrow_pda(&trip_account);
+    program_ix(
+        driver_trip_reward::accounts::CreateTripWithFare {
+            fee_payer: *payer,
+            trip_account,
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            passenger: *passenger,
+            passenger_account: passenger_pda(passenger),
+            fare_escrow,
+            fare_mint: token.map(|(mint, _)| *mint),
+            passenger_token_account: token.map(|(_, account)| *account),
+            fare_vault: token.map(|(mint, _)| associated_token_address(&fare_escrow, mint)),
+            token_program: token.map(|_| spl_token_2022::ID),
+            associated_token_program: token.map(|_| spl_associated_token_account::ID),
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::CreateTripWithFare { trip_id, start_time, fare },
+    )
+}
+
+pub fn initialize_passenger_ix(payer: &Pubkey, passenger: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializePassenger {
+            fee_payer: *payer,
+            passenger_account: passenger_pda(passenger),
+            passenger_pubkey: *passenger,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializePassenger {},
+    )
+}
+
+pub fn rate_trip_ix(passenger: &Pubkey, driver: &Pubkey, trip_id: u64, rating: u8) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::RateTrip {
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            passenger_account: passenger_pda(passenger),
+            passenger: *passenger,
+        },
+        driver_trip_reward::instruction::RateTrip { trip_id, rating },
+    )
+}
+
+pub fn cancel_trip_ix(authority: &Pubkey, driver: &Pubkey, trip_id: u64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CancelTrip {
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *authority,
+            session: None,
+            passenger_account: None,
+            fare_escrow: None,
+            passenger: None,
+            fare_mint: None,
+            fare_vault: None,
+            fare_token_account: None,
+            token_program: None,
+        },
+        driver_trip_reward::instruction::CancelTrip { trip_id },
+    )
+}
+
+/// Settles the fare escrow of `trip_account` in a `complete_trip` or
+/// `cancel_trip` instruction. Token fares pass `(mint, token_account)` for the
+/// recipient. The passenger and escrow accounts are the trailing optional accounts.
+pub fn with_fare_escrow(mut ix: Instruction, trip_account: &Pubkey, passenger: &Pubkey, token: Option<(&Pubkey, &Pubkey)>) -> Instruction {
+    let fare_escrow = fare_escrow_pda(trip_account);
+    let first = ix.accounts.len() - 7;
+    assert!(ix.accounts[first..].iter().all(|meta| meta.pubkey == driver_trip_reward::ID));
+    ix.accounts[first] = AccountMeta::new(passenger_pda(passenger), false);
+    ix.accounts[first + 1] = AccountMeta::new(fare_escrow, false);
+    ix.accounts[first + 2] = AccountMeta::new(*passenger, false);
+    if let Some((mint, token_account)) = token {
+        ix.accounts[first + 3] = AccountMeta::new_readonly(*mint, false);
+        ix.accounts[first + 4] = AccountMeta::new(associated_token_address(&fare_escrow, mint), false);
+        ix.accounts[first + 5] = AccountMeta::new(*token_account, false);
+        ix.accounts[first + 6] = AccountMeta::new_readonly(spl_token_2022::ID, false);
+    }
+    ix
+}
+
+/// `next_trip_nonce` must be the driver's current nonce; the trip id is
+/// `NONCE_TRIP_ID_BASE + next_trip_nonce`.
+#[allow(clippy::too_many_arguments)]
+pub fn submit_next_trip_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    next_trip_nonce: u64,
+    passenger: Pubkey,
+    start_time: i64,
+    distance: u64,
+    duration: u64,
+    fare: u64,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SubmitNextTrip {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            trip_account: trip_pda(driver, NONCE_TRIP_ID_BASE + next_trip_nonce),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::SubmitNextTrip {
+            passenger_pubkey: passenger,
+            start_time,
+            distance,
+            duration,
+            fare,
+        },
+    )
+}
+
+pub fn create_next_trip_ix(payer: &Pubkey, driver: &Pubkey, next_trip_nonce: u64, passenger: Pubkey, start_time: i64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CreateNextTrip {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            trip_account: trip_pda(driver, NONCE_TRIP_ID_BASE + next_trip_nonce),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::CreateNextTrip {
+            passenger_pubkey: passenger,
+            start_time,
+        },
+    )
+}
+
+pub fn add_trip_leg_ix(payer: &Pubkey, driver: &Pubkey, trip_id: u64, start_time: i64, end_time: i64, distance: u64, passenger: Option<Pubkey>) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::AddTripLeg {
+            fee_payer: *payer,
+            sponsor_treasury: None,
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::AddTripLeg {
+            trip_id,
+            start_time,
+            end_time,
+            distance,
+            passenger,
+        },
+    )
+}
+
+pub fn verify_trip_ix(payer: &Pubkey, driver: &Pubkey, trip_id: u64, end_time: i64, rating: f32, trip_hash: [u8; 32]) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::VerifyTrip {
+            fee_payer: *payer,
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            scoring_config: scoring_config_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            trip_hash_record: trip_hash_record_pda(&trip_hash),
+            system_program: system_program::ID,
+            reward_pool_account: None,
+        },
+        driver_trip_reward::instruction::VerifyTrip {
+            trip_id,
+            end_time,
+            rating,
+            trip_hash,
+        },
+    )
+}
+
+/// Binds the trip of a `verify_trip` instruction to `pool_id`; the optional
+/// `reward_pool_account` is its last account.
+pub fn with_reward_pool(mut ix: Instruction, pool_id: u64) -> Instruction {
+    let last = ix.accounts.len() - 1;
+    assert_eq!(ix.accounts[last].pubkey, driver_trip_reward::ID);
+    ix.accounts[last] = AccountMeta::new_readonly(reward_pool_pda(pool_id), false);
+    ix
+}
+
+/// Has the passenger co-sign a `create_trip` or `submit_trip` instruction; the
+/// optional `passenger` account is its last account.
+pub fn with_passenger(mut ix: Instruction, passenger: &Pubkey) -> Instruction {
+    let last = ix.accounts.len() - 1;
+    assert_eq!(ix.accounts[last].pubkey, driver_trip_reward::ID);
+    ix.accounts[last] = AccountMeta::new_readonly(*passenger, true);
+    ix
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn complete_trip_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    trip_id: u64,
+    end_time: i64,
+    distance: u64,
+    duration: u64,
+    fare: u64,
+    rating: f32,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CompleteTrip {
+            fee_payer: *payer,
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            scoring_config: scoring_config_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+            passenger_account: None,
+            fare_escrow: None,
+            passenger: None,
+            fare_mint: None,
+            fare_vault: None,
+            fare_token_account: None,
+            token_program: None,
+        },
+        driver_trip_reward::instruction::CompleteTrip {
+            trip_id,
+            end_time,
+            distance,
+            duration,
+            fare,
+            rating,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_reward_pool_ix(
+    payer: &Pubkey,
+    admin: &Pubkey,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    pool_id: u64,
+    total_rewards: u64,
+    reward_per_trip: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::InitializeRewardPool {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool,
+            vault: *vault,
+            vault_authority: vault_authority_pda(&reward_pool),
+            reward_mint: *reward_mint,
+            admin_pubkey: *admin,
+            token_program: spl_token_2022::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        driver_trip_reward::instruction::InitializeRewardPool {
+            pool_id,
+            total_rewards,
+            reward_per_trip,
+            start_time,
+            end_time,
+        },
+    )
+}
+
+pub fn claim_rewards_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    pool_id: u64,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    driver_token_account: &Pubkey,
+) -> Instruction {
+    claim_rewards_for_ix(payer, driver, pool_id, vault, reward_mint, driver, driver_token_account)
+}
+
+/// `claim_rewards_ix` paying into the Token-2022 associated token account of
+/// `destination_owner`, which the claim creates if needed.
+pub fn claim_rewards_to_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    pool_id: u64,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    destination_owner: &Pubkey,
+) -> Instruction {
+    let destination = associated_token_address(destination_owner, reward_mint);
+    claim_rewards_for_ix(payer, driver, pool_id, vault, reward_mint, destination_owner, &destination)
+}
+
+fn claim_rewards_for_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    pool_id: u64,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    destination_owner: &Pubkey,
+    driver_token_account: &Pubkey,
+) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::ClaimRewards {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool,
+            reward_mint: *reward_mint,
+            vault: *vault,
+            destination_owner: *destination_owner,
+            driver_token_account: *driver_token_account,
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            vault_authority: vault_authority_pda(&reward_pool),
+            token_program: spl_token_2022::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            driver_pool_state: driver_pool_pda(&reward_pool, driver),
+            system_program: system_program::ID,
+            config: config_pda(),
+            treasury_token_account: None,
+            reward_split: None,
+            trip_account: None,
+        },
+        driver_trip_reward::instruction::ClaimRewards { pool_id },
+    )
+}
+
+/// `claim_rewards_ix` that also marks `trip_id` as rewarded.
+pub fn claim_trip_rewards_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    pool_id: u64,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    driver_token_account: &Pubkey,
+    trip_id: u64,
+) -> Instruction {
+    with_trip_receipt(claim_rewards_ix(payer, driver, pool_id, vault, reward_mint, driver_token_account), driver, trip_id)
+}
+
+/// Marks `trip_id` as rewarded by a claim; the optional `trip_account` is the
+/// last named account of both claim instructions.
+pub fn with_trip_receipt(mut ix: Instruction, driver: &Pubkey, trip_id: u64) -> Instruction {
+    let trip = ix.accounts.iter().rposition(|meta| meta.pubkey == driver_trip_reward::ID).expect("instruction has no trip placeholder");
+    ix.accounts[trip] = AccountMeta::new(trip_pda(driver, trip_id), false);
+    ix
+}
+
+/// Passes the driver's reward split to a claim, followed by the accounts paying
+/// its beneficiaries in split order. The optional `reward_split` account
+/// directly follows the treasury.
+pub fn with_reward_split(mut ix: Instruction, driver: &Pubkey, beneficiary_accounts: &[Pubkey]) -> Instruction {
+    let config = ix.accounts.iter().position(|meta| meta.pubkey == config_pda()).expect("instruction has no config");
+    assert_eq!(ix.accounts[config + 2].pubkey, driver_trip_reward::ID);
+    ix.accounts[config + 2] = AccountMeta::new(reward_split_pda(driver), false);
+    ix.accounts.extend(beneficiary_accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    ix
+}
+
+pub fn set_reward_split_ix(payer: &Pubkey, driver: &Pubkey, shares: Vec<SplitShare>) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SetRewardSplit {
+            fee_payer: *payer,
+            reward_split: reward_split_pda(driver),
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::SetRewardSplit { shares },
+    )
+}
+
+/// Pays the protocol fee of a claim to `treasury`: the treasury's token account
+/// for token pools, or the treasury wallet for SOL pools. The optional account
+/// directly follows `config`.
+pub fn with_treasury(mut ix: Instruction, treasury: &Pubkey) -> Instruction {
+    let config = ix.accounts.iter().position(|meta| meta.pubkey == config_pda()).expect("instruction has no config");
+    assert_eq!(ix.accounts[config + 1].pubkey, driver_trip_reward::ID);
+    ix.accounts[config + 1] = AccountMeta::new(*treasury, false);
+    ix
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_sol_reward_pool_ix(
+    payer: &Pubkey,
+    admin: &Pubkey,
+    pool_id: u64,
+    total_rewards: u64,
+    reward_per_trip: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::InitializeSolRewardPool {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool,
+            sol_vault: sol_vault_pda(&reward_pool),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeSolRewardPool {
+            pool_id,
+            total_rewards,
+            reward_per_trip,
+            start_time,
+            end_time,
+        },
+    )
+}
+
+pub fn fund_sol_reward_pool_ix(funder: &Pubkey, pool_id: u64, amount: u64) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::FundSolRewardPool {
+            funder: *funder,
+            reward_pool_account: reward_pool,
+            sol_vault: sol_vault_pda(&reward_pool),
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::FundSolRewardPool { pool_id, amount },
+    )
+}
+
+pub fn claim_sol_rewards_ix(payer: &Pubkey, driver: &Pubkey, pool_id: u64, destination: &Pubkey) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::ClaimSolRewards {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool,
+            sol_vault: sol_vault_pda(&reward_pool),
+            destination: *destination,
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            driver_pool_state: driver_pool_pda(&reward_pool, driver),
+            system_program: system_program::ID,
+            config: config_pda(),
+            treasury: None,
+            reward_split: None,
+            trip_account: None,
+        },
+        driver_trip_reward::instruction::ClaimSolRewards { pool_id },
+    )
+}
+
+pub fn set_protocol_fee_ix(admin: &Pubkey, fee_bps: u16, treasury: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SetProtocolFee {
+            config: config_pda(),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::SetProtocolFee { fee_bps, treasury: *treasury },
+    )
+}
+
+pub fn set_pool_claim_limits_ix(
+    admin: &Pubkey,
+    pool_id: u64,
+    daily_cap_per_driver: u64,
+    epoch_cap_per_driver: u64,
+    claim_cooldown: i64,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateRewardPool {
+            config: config_pda(),
+            reward_pool_account: reward_pool_pda(pool_id),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::SetPoolClaimLimits {
+            pool_id,
+            daily_cap_per_driver,
+            epoch_cap_per_driver,
+            claim_cooldown,
+        },
+    )
+}
+
+pub fn set_validator_fee_ix(admin: &Pubkey, pool_id: u64, validator_fee: u64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateRewardPool {
+            config: config_pda(),
+            reward_pool_account: reward_pool_pda(pool_id),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::SetValidatorFee { pool_id, validator_fee },
+    )
+}
+
+pub fn finalize_trip_ix(payer: &Pubkey, validator: &Pubkey, pool_id: u64, driver: &Pubkey, trip_id: u64) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::FinalizeTrip {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool,
+            trip_account: trip_pda(driver, trip_id),
+            validator_account: validator_pda(validator),
+            validator_fee_balance: validator_fees_pda(&reward_pool, validator),
+            validator_pubkey: *validator,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::FinalizeTrip { pool_id, trip_id },
+    )
+}
+
+pub fn claim_validator_fees_ix(
+    validator: &Pubkey,
+    pool_id: u64,
+    vault: &Pubkey,
+    reward_mint: &Pubkey,
+    validator_token_account: &Pubkey,
+) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::ClaimValidatorFees {
+            reward_pool_account: reward_pool,
+            reward_mint: *reward_mint,
+            vault: *vault,
+            validator_token_account: *validator_token_account,
+            validator_fee_balance: validator_fees_pda(&reward_pool, validator),
+            validator_pubkey: *validator,
+            vault_authority: vault_authority_pda(&reward_pool),
+            token_program: spl_token_2022::ID,
+        },
+        driver_trip_reward::instruction::ClaimValidatorFees { pool_id },
+    )
+}
+
+pub fn claim_sol_validator_fees_ix(validator: &Pubkey, pool_id: u64) -> Instruction {
+    let reward_pool = reward_pool_pda(pool_id);
+    program_ix(
+        driver_trip_reward::accounts::ClaimSolValidatorFees {
+            reward_pool_account: reward_pool,
+            sol_vault: sol_vault_pda(&reward_pool),
+            validator_fee_balance: validator_fees_pda(&reward_pool, validator),
+            validator_pubkey: *validator,
+        },
+        driver_trip_reward::instruction::ClaimSolValidatorFees { pool_id },
+    )
+}
+
+pub fn distribute_reward_ix(payer: &Pubkey, driver: &Pubkey, pool_id: u64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::DistributeReward {
+            fee_payer: *payer,
+            reward_pool_account: reward_pool_pda(pool_id),
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+        },
+        driver_trip_reward::instruction::DistributeReward { pool_id },
+    )
+}
+
+pub fn initialize_validator_ix(payer: &Pubkey, validator: &Pubkey, public_key: &str, private_key: &str) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeValidator {
+            fee_payer: *payer,
+            validator_account: validator_pda(validator),
+            validator_pubkey: *validator,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeValidator {
+            public_key: public_key.to_string(),
+            private_key: private_key.to_string(),
+        },
+    )
+}
+
+pub fn set_validator_active_ix(admin: &Pubkey, validator: &Pubkey, is_active: bool) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SetValidatorActive {
+            config: config_pda(),
+            validator_account: validator_pda(validator),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::SetValidatorActive { is_active },
+    )
+}
+
+pub fn process_private_data_ix(payer: &Pubkey, validator: &Pubkey, data: &str, operation: &str) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::ProcessPrivateData {
+            fee_payer: *payer,
+            validator_account: validator_pda(validator),
+            validator_pubkey: *validator,
+        },
+        driver_trip_reward::instruction::ProcessPrivateData {
+            data: data.to_string(),
+            operation: operation.to_string(),
+        },
+    )
+}
+
+pub fn update_validator_weights_ix(payer: &Pubkey, validator: &Pubkey, new_weights: Vec<u8>) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateValidatorWeights {
+            fee_payer: *payer,
+            validator_account: validator_pda(validator),
+            validator_pubkey: *validator,
+        },
+        driver_trip_reward::instruction::UpdateValidatorWeights { new_weights },
+    )
+}
+
+pub fn initialize_config_ix(payer: &Pubkey, admin: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeConfig {
+            fee_payer: *payer,
+            config: config_pda(),
+            admin_pubkey: *admin,
+            program_data: program_data_pda(),
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeConfig {},
+    )
+}
+
+pub fn suspend_driver_ix(
+    payer: &Pubkey,
+    admin: &Pubkey,
+    driver: &Pubkey,
+    suspension_index: u32,
+    reason_code: u8,
+    expires_at: Option<i64>,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::SuspendDriver {
+            fee_payer: *payer,
+            config: config_pda(),
+            driver_account: driver_pda(driver),
+            suspension_record: suspension_pda(driver, suspension_index),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::SuspendDriver { reason_code, expires_at },
+    )
+}
+
+pub fn reactivate_driver_ix(payer: &Pubkey, admin: &Pubkey, driver: &Pubkey, suspension_index: u32) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::ReactivateDriver {
+            fee_payer: *payer,
+            config: config_pda(),
+            driver_account: driver_pda(driver),
+            suspension_record: suspension_pda(driver, suspension_index),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::ReactivateDriver {},
+    )
+}
+
+pub fn initialize_safety_config_ix(payer: &Pubkey, admin: &Pubkey, penalties: [u32; 3], review_threshold: u32) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeSafetyConfig {
+            fee_payer: *payer,
+            config: config_pda(),
+            safety_config: safety_config_pda(),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeSafetyConfig { penalties, review_threshold },
+    )
+}
+
+pub fn update_safety_config_ix(admin: &Pubkey, penalties: [u32; 3], review_threshold: u32) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateSafetyConfig {
+            config: config_pda(),
+            safety_config: safety_config_pda(),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::UpdateSafetyConfig { penalties, review_threshold },
+    )
+}
+
+pub fn report_unsafe_driving_ix(passenger: &Pubkey, driver: &Pubkey, trip_id: u64, category: u8, evidence_hash: [u8; 32]) -> Instruction {
+    let trip_account = trip_pda(driver, trip_id);
+    program_ix(
+        driver_trip_reward::accounts::ReportUnsafeDriving {
+            trip_account,
+            driver_account: driver_pda(driver),
+            safety_config: safety_config_pda(),
+            report: unsafe_report_pda(&trip_account),
+            driver_pubkey: *driver,
+            passenger_account: passenger_pda(passenger),
+            passenger: *passenger,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::ReportUnsafeDriving {
+            trip_id,
+            category,
+            evidence_hash,
+        },
+    )
+}
+
+pub fn clear_driver_review_ix(admin: &Pubkey, driver: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::ClearDriverReview {
+            config: config_pda(),
+            driver_account: driver_pda(driver),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::ClearDriverReview {},
+    )
+}
+
+pub fn initialize_trip_rules_ix(
+    payer: &Pubkey,
+    admin: &Pubkey,
+    min_distance: u64,
+    max_distance: u64,
+    min_duration: u64,
+    min_avg_speed: u64,
+    max_avg_speed: u64,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeTripRules {
+            fee_payer: *payer,
+            config: config_pda(),
+            trip_rules: trip_rules_pda(),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeTripRules {
+            min_distance,
+            max_distance,
+            min_duration,
+            min_avg_speed,
+            max_avg_speed,
+        },
+    )
+}
+
+pub fn update_trip_rules_ix(
+    admin: &Pubkey,
+    min_distance: u64,
+    max_distance: u64,
+    min_duration: u64,
+    min_avg_speed: u64,
+    max_avg_speed: u64,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateTripRules {
+            config: config_pda(),
+            trip_rules: trip_rules_pda(),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::UpdateTripRules {
+            min_distance,
+            max_distance,
+            min_duration,
+            min_avg_speed,
+            max_avg_speed,
+        },
+    )
+}
+
+pub fn initialize_scoring_config_ix(payer: &Pubkey, admin: &Pubkey, weights: ScoringWeights) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeScoringConfig {
+            fee_payer: *payer,
+            config: config_pda(),
+            scoring_config: scoring_config_pda(),
+            scoring_version: scoring_version_pda(1),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeScoringConfig { weights },
+    )
+}
+
+/// Publishes `weights` as `version`, which must be the current version plus one.
+pub fn publish_scoring_config_ix(payer: &Pubkey, admin: &Pubkey, version: u32, weights: ScoringWeights) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::PublishScoringConfig {
+            fee_payer: *payer,
+            config: config_pda(),
+            scoring_config: scoring_config_pda(),
+            scoring_version: scoring_version_pda(version),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::PublishScoringConfig { weights },
+    )
+}
+
+pub fn commit_telemetry_chunk_ix(driver: &Pubkey, trip_id: u64, chunk_index: u32, chunk_hash: [u8; 32]) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CommitTelemetryChunk {
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+        },
+        driver_trip_reward::instruction::CommitTelemetryChunk {
+            trip_id,
+            chunk_index,
+            chunk_hash,
+        },
+    )
+}
+
+pub fn checkpoint_trip_ix(driver: &Pubkey, trip_id: u64, distance: u64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CheckpointTrip {
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            trip_rules: trip_rules_pda(),
+            driver_pubkey: *driver,
+            authority: *driver,
+            session: None,
+        },
+        driver_trip_reward::instruction::CheckpointTrip { trip_id, distance },
+    )
+}
+
+pub fn create_session_ix(
+    payer: &Pubkey,
+    driver: &Pubkey,
+    session_key: Pubkey,
+    expires_at: i64,
+    allowed_instructions: u32,
+) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::CreateSession {
+            fee_payer: *payer,
+            session: session_pda(driver, &session_key),
+            driver_account: driver_pda(driver),
+            driver_pubkey: *driver,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::CreateSession {
+            session_key,
+            expires_at,
+            allowed_instructions,
+        },
+    )
+}
+
+pub fn revoke_session_ix(driver: &Pubkey, session_key: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::RevokeSession {
+            session: session_pda(driver, session_key),
+            driver_pubkey: *driver,
+        },
+        driver_trip_reward::instruction::RevokeSession {},
+    )
+}
+
+pub fn initialize_sponsor_treasury_ix(payer: &Pubkey, admin: &Pubkey, per_driver_budget: u64, relayer: &Pubkey) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::InitializeSponsorTreasury {
+            fee_payer: *payer,
+            config: config_pda(),
+            sponsor_treasury: sponsor_treasury_pda(),
+            admin_pubkey: *admin,
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::InitializeSponsorTreasury {
+            per_driver_budget,
+            relayer: *relayer,
+        },
+    )
+}
+
+pub fn update_sponsor_treasury_ix(admin: &Pubkey, per_driver_budget: u64, relayer: &Pubkey, is_active: bool) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateSponsorTreasury {
+            config: config_pda(),
+            sponsor_treasury: sponsor_treasury_pda(),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::UpdateSponsorTreasury {
+            per_driver_budget,
+            relayer: *relayer,
+            is_active,
+        },
+    )
+}
+
+pub fn fund_sponsor_treasury_ix(funder: &Pubkey, amount: u64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::FundSponsorTreasury {
+            funder: *funder,
+            sponsor_treasury: sponsor_treasury_pda(),
+            system_program: system_program::ID,
+        },
+        driver_trip_reward::instruction::FundSponsorTreasury { amount },
+    )
+}
+
+/// Closes `trip_id`, refunding rent to `rent_payer`, which may be the sponsor treasury.
+pub fn close_trip_ix(driver: &Pubkey, trip_id: u64, rent_payer: &Pubkey) -> Instruction {
+    let sponsored = *rent_payer == sponsor_treasury_pda();
+    program_ix(
+        driver_trip_reward::accounts::CloseTrip {
+            trip_account: trip_pda(driver, trip_id),
+            driver_account: driver_pda(driver),
+            sponsor_treasury: sponsored.then(sponsor_treasury_pda),
+            rent_payer: (!sponsored).then_some(*rent_payer),
+        },
+        driver_trip_reward::instruction::CloseTrip { trip_id },
+    )
+}
+
+pub fn set_clock_tolerances_ix(admin: &Pubkey, max_clock_drift: i64, max_trip_age: i64) -> Instruction {
+    program_ix(
+        driver_trip_reward::accounts::UpdateTripRules {
+            config: config_pda(),
+            trip_rules: trip_rules_pda(),
+            admin_pubkey: *admin,
+        },
+        driver_trip_reward::instruction::SetClockTolerances { max_clock_drift, max_trip_age },
+    )
+}
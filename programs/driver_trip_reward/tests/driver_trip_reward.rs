@@ -0,0 +1,2825 @@
+//! Integration tests against the SBF build of the program. Run with `cargo test-sbf`.
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::*;
+use driver_trip_reward::{
+    DriverAccount, DriverPoolState, DriverTripRewardError, FareEscrow, PassengerAccount, ProgramConfig, RewardPoolAccount, RewardSplit, SplitShare, ScoringConfig, ScoringVersion, TripLeg, ScoringWeights, SessionAccount,
+    SponsorTreasury, SuspensionRecord, TripAccount, UnsafeDrivingReport, TripHashRecord, TripRules, ValidatorAccount, ValidatorFeeBalance, SESSION_SCOPE_ALL, SESSION_SCOPE_CHECKPOINT_TRIP, SESSION_SCOPE_COMMIT_TELEMETRY,
+    SESSION_SCOPE_CREATE_TRIP, SESSION_SCOPE_SUBMIT_TRIP, TRIP_DISPUTE_WINDOW, DEFAULT_MAX_CLOCK_DRIFT,
+    DEFAULT_MAX_TRIP_AGE, MIN_CHECKPOINT_INTERVAL, MAX_PROTOCOL_FEE_BPS, NEUTRAL_RATING, PASSENGER_RATING_WINDOW, UNSAFE_DRIVING_HARSH_BRAKING, UNSAFE_DRIVING_PHONE_USE, UNSAFE_DRIVING_SPEEDING, MAX_SPLIT_BENEFICIARIES, MAX_TRIP_LEGS, NONCE_TRIP_ID_BASE, SECONDS_PER_DAY, TRIP_LEG_SPACE,
+};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::ExtensionType,
+    instruction::{initialize_non_transferable_mint, initialize_permanent_delegate},
+};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const TRIP_HASH: [u8; 32] = [1u8; 32];
+const CHUNK_HASH: [u8; 32] = [7u8; 32];
+
+async fn setup_driver(env: &mut TestEnv) -> Keypair {
+    let driver = Keypair::new();
+    let payer = env.payer().pubkey();
+    env.send(&[initialize_driver_ix(&payer, &driver.pubkey())], &[&driver])
+        .await
+        .unwrap();
+    driver
+}
+
+async fn setup_passenger(env: &mut TestEnv) -> Keypair {
+    let passenger = env.funded_keypair(1_000_000_000).await;
+    let payer = env.payer().pubkey();
+    env.send(&[initialize_passenger_ix(&payer, &passenger.pubkey())], &[&passenger])
+        .await
+        .unwrap();
+    passenger
+}
+
+async fn setup_verified_trip(env: &mut TestEnv, driver: &Keypair, trip_id: u64) {
+    setup_verified_pool_trip(env, driver, trip_id, None).await;
+}
+
+/// `setup_verified_trip`, binding the trip to `pool_id` when given.
+async fn setup_verified_pool_trip(env: &mut TestEnv, driver: &Keypair, trip_id: u64, pool_id: Option<u64>) {
+    let payer = env.payer().pubkey();
+    let mut verify = verify_trip_ix(&payer, &driver.pubkey(), trip_id, START_TIME + 1_800, 4.5, telemetry_chain(&[CHUNK_HASH]));
+    if let Some(pool_id) = pool_id {
+        verify = with_reward_pool(verify, pool_id);
+    }
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[
+            submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, 12, 1_800, 200_000_000),
+            commit_telemetry_chunk_ix(&driver.pubkey(), trip_id, 0, CHUNK_HASH),
+            verify,
+        ],
+        &[driver],
+    )
+    .await
+    .unwrap();
+}
+
+/// Opens `trip_id` with `passenger` co-signing an escrowed fare and completes it.
+async fn setup_fare_trip(env: &mut TestEnv, driver: &Keypair, passenger: &Keypair, trip_id: u64, start_time: i64, rating: f32) {
+    let payer = env.payer().pubkey();
+    env.set_unix_timestamp(start_time).await;
+    env.send(
+        &[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), trip_id, start_time, 50_000_000)],
+        &[driver, passenger],
+    )
+    .await
+    .unwrap();
+    env.set_unix_timestamp(start_time + 900).await;
+    let complete = complete_trip_ix(&payer, &driver.pubkey(), trip_id, start_time + 900, 8, 900, 50_000_000, rating);
+    env.send(&[with_fare_escrow(complete, &trip_pda(&driver.pubkey(), trip_id), &passenger.pubkey(), None)], &[driver])
+        .await
+        .unwrap();
+}
+
+struct Pool {
+    pool_id: u64,
+    mint: Pubkey,
+    vault: Pubkey,
+}
+
+async fn setup_pool(env: &mut TestEnv, pool_id: u64, total_rewards: u64, reward_per_trip: u64, funded: u64) -> Pool {
+    let mint = env.create_mint().await;
+    setup_pool_with_mint(env, pool_id, total_rewards, reward_per_trip, funded, mint).await
+}
+
+async fn setup_pool_with_mint(
+    env: &mut TestEnv,
+    pool_id: u64,
+    total_rewards: u64,
+    reward_per_trip: u64,
+    funded: u64,
+    mint: Pubkey,
+) -> Pool {
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+    let vault = Keypair::new();
+    let token_program = env.owner(mint).await;
+    let ix = initialize_reward_pool_ix(
+        &payer,
+        &admin.pubkey(),
+        &vault.pubkey(),
+        &mint,
+        pool_id,
+        total_rewards,
+        reward_per_trip,
+        START_TIME,
+        START_TIME + 86_400,
+    );
+    env.send(&[with_token_program(ix, &token_program)], &[&admin, &vault])
+        .await
+        .unwrap();
+    env.mint_to(mint, vault.pubkey(), funded).await;
+    Pool {
+        pool_id,
+        mint,
+        vault: vault.pubkey(),
+    }
+}
+
+// -----------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn only_the_upgrade_authority_initializes_config() {
+    let mut env = TestEnv::start().await;
+    let admin = env.admin.insecure_clone();
+    let stranger = Keypair::new();
+    let payer = env.payer().pubkey();
+
+    let result = env.send(&[initialize_config_ix(&payer, &stranger.pubkey())], &[&stranger]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    assert!(!env.account_exists(config_pda()).await);
+    env.send(&[initialize_config_ix(&payer, &admin.pubkey())], &[&admin])
+        .await
+        .unwrap();
+    let config: ProgramConfig = env.fetch(config_pda()).await;
+    assert_eq!(config.admin_pubkey, admin.pubkey());
+}
+
+// -----------------------------------------------------------------
+// Drivers
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn initialize_driver_sets_defaults() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.driver_pubkey, driver.pubkey());
+    assert_eq!(account.total_trips, 0);
+    assert_eq!(account.total_earnings, 0);
+    assert_eq!(account.rating, 0.0);
+    assert!(account.is_active);
+}
+
+#[tokio::test]
+async fn initialize_driver_twice_fails() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+
+    // `init` rejects the existing PDA before `DriverAlreadyExists` can be reached.
+    env.refresh_blockhash().await;
+    let result = env.send(&[initialize_driver_ix(&payer, &driver.pubkey())], &[&driver]).await;
+    assert!(result.is_err());
+}
+
+// -----------------------------------------------------------------
+// Trips
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn submit_trip_records_trip() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let passenger = Pubkey::new_unique();
+
+    env.send(
+        &[submit_trip_ix(&payer, &driver.pubkey(), passenger, 7, START_TIME, 10, 3_600, 2_000_000_000)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 7)).await;
+    assert_eq!(trip.trip_id, 7);
+    assert_eq!(trip.driver_pubkey, driver.pubkey());
+    assert_eq!(trip.passenger_pubkey, passenger);
+    assert_eq!(trip.start_time, START_TIME);
+    assert_eq!(trip.distance, 10);
+    assert_eq!(trip.duration, 3_600);
+    assert_eq!(trip.fare, 2_000_000_000);
+    assert_eq!(trip.status, 0);
+}
+
+#[tokio::test]
+async fn submit_trip_rejects_zero_values() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let passenger = Pubkey::new_unique();
+
+    let cases = [
+        (0, 10, 3_600, 1_000),
+        (START_TIME, 0, 3_600, 1_000),
+        (START_TIME, 10, 0, 1_000),
+        (START_TIME, 10, 3_600, 0),
+    ];
+    for (trip_id, (start_time, distance, duration, fare)) in cases.into_iter().enumerate() {
+        let ix = submit_trip_ix(&payer, &driver.pubkey(), passenger, trip_id as u64, start_time, distance, duration, fare);
+        let result = env.send(&[ix], &[&driver]).await;
+        assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+    }
+}
+
+#[tokio::test]
+async fn create_trip_opens_pending_trip() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let passenger = Pubkey::new_unique();
+
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), passenger, 3, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 3)).await;
+    assert_eq!(trip.trip_id, 3);
+    assert_eq!(trip.passenger_pubkey, passenger);
+    assert_eq!(trip.distance, 0);
+    assert_eq!(trip.status, 0);
+}
+
+#[tokio::test]
+async fn create_trip_rejects_invalid_start_time() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+
+    let result = env
+        .send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 3, 0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn next_trip_nonce_assigns_ids_around_legacy_ids() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let passenger = Pubkey::new_unique();
+
+    let opens: [Box<dyn Fn(i64) -> Instruction>; 5] = [
+        Box::new(|start_time| submit_next_trip_ix(&payer, &driver.pubkey(), 0, passenger, start_time, 12, 1_800, 1_000)),
+        Box::new(|start_time| create_next_trip_ix(&payer, &driver.pubkey(), 1, passenger, start_time)),
+        // Legacy ids live below the nonce-assigned range and leave the nonce alone.
+        Box::new(|start_time| create_trip_ix(&payer, &driver.pubkey(), passenger, 5, start_time)),
+        Box::new(|start_time| create_next_trip_ix(&payer, &driver.pubkey(), 2, passenger, start_time)),
+        Box::new(|start_time| create_trip_ix(&payer, &driver.pubkey(), passenger, 0, start_time)),
+    ];
+    for (step, open) in opens.iter().enumerate() {
+        // Each trip is left to go stale so the next one may open.
+        let now = START_TIME + step as i64 * (DEFAULT_MAX_TRIP_AGE + 1);
+        env.set_unix_timestamp(now).await;
+        env.send(&[open(now)], &[&driver]).await.unwrap();
+    }
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.next_trip_nonce, 3);
+    for trip_id in [0, 5, NONCE_TRIP_ID_BASE, NONCE_TRIP_ID_BASE + 1, NONCE_TRIP_ID_BASE + 2] {
+        let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), trip_id)).await;
+        assert_eq!(trip.trip_id, trip_id);
+        assert_eq!(trip.driver_pubkey, driver.pubkey());
+    }
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), NONCE_TRIP_ID_BASE)).await;
+    assert_eq!(trip.distance, 12);
+    assert_eq!(trip.fare, 1_000);
+
+    // Legacy ids cannot reach into the nonce-assigned range.
+    let now = START_TIME + 5 * (DEFAULT_MAX_TRIP_AGE + 1);
+    env.set_unix_timestamp(now).await;
+    for trip_id in [NONCE_TRIP_ID_BASE + 3, u64::MAX - 1] {
+        let result = env.send(&[create_trip_ix(&payer, &driver.pubkey(), passenger, trip_id, now)], &[&driver]).await;
+        assert_program_error(result, DriverTripRewardError::InvalidTripId);
+    }
+
+    // A stale nonce derives the wrong trip address.
+    let result = env.send(&[create_next_trip_ix(&payer, &driver.pubkey(), 2, passenger, now)], &[&driver]).await;
+    assert!(result.is_err());
+    env.send(&[create_next_trip_ix(&payer, &driver.pubkey(), 3, passenger, now)], &[&driver])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn verify_trip_scores_and_updates_driver() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.status, 1);
+    assert_eq!(trip.verification_status, 1);
+    assert_eq!(trip.end_time, START_TIME + 1_800);
+    assert_eq!(trip.trip_hash, telemetry_chain(&[CHUNK_HASH]));
+    assert_eq!(trip.telemetry_chunks, 1);
+    // 4.5 * 20 + 50 + 30 + 20, capped at 100.
+    assert_eq!(trip.score, 100);
+    assert_eq!(trip.scoring_version, 1);
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_trips, 1);
+    assert_eq!(account.completed_trips, 1);
+    // Self-reported fares are never settled, so they are not earnings.
+    assert_eq!(account.total_earnings, 0);
+    assert_eq!(account.total_distance, 12);
+    assert_eq!(account.total_time, 1_800);
+    assert_eq!(account.total_score, 100);
+    // Unrated by its passenger, the trip counts as neutral.
+    assert_eq!(account.rating, NEUTRAL_RATING);
+    assert_eq!(account.last_trip_time, START_TIME + 1_800);
+}
+
+#[tokio::test]
+async fn verify_trip_rejects_invalid_input() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(
+        &[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 10, 600, 1_000)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    env.set_unix_timestamp(START_TIME + 600).await;
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME, 4.0, TRIP_HASH)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 600, 5.5, TRIP_HASH)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn verify_trip_rejects_already_verified_trip() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    setup_verified_trip(&mut env, &driver, 1).await;
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 3.0, TRIP_HASH)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn complete_trip_updates_driver() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 900).await;
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, 50_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 2)).await;
+    assert_eq!(trip.status, 1);
+    assert_eq!(trip.start_time, START_TIME);
+    assert_eq!(trip.end_time, START_TIME + 900);
+    assert_eq!(trip.distance, 8);
+    assert_eq!(trip.fare, 50_000);
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_trips, 1);
+    assert_eq!(account.completed_trips, 0);
+    assert_eq!(account.total_earnings, 0);
+    assert_eq!(account.total_distance, 8);
+    assert_eq!(account.rating, NEUTRAL_RATING);
+}
+
+#[tokio::test]
+async fn complete_trip_rejects_invalid_input() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 900).await;
+
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME - 1, 8, 900, 50_000, 4.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, 50_000, -1.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, 50_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 901, 8, 900, 50_000, 4.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn passenger_ratings_feed_the_driver_rating() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    let trip_account = trip_pda(&driver.pubkey(), 2);
+    env.send(
+        &[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 50_000_000)],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    let result = env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 2, 5)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+    env.set_unix_timestamp(START_TIME + 900).await;
+    let complete = complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, 50_000_000, 1.0);
+    env.send(&[with_fare_escrow(complete, &trip_account, &passenger.pubkey(), None)], &[&driver])
+        .await
+        .unwrap();
+
+    let stranger = setup_passenger(&mut env).await;
+    let result = env.send(&[rate_trip_ix(&stranger.pubkey(), &driver.pubkey(), 2, 5)], &[&stranger]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let result = env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 2, 6)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRating);
+    env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 2, 5)], &[&passenger])
+        .await
+        .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 2)).await;
+    assert_eq!(trip.passenger_rating, 5);
+    assert_eq!(trip.rated_at, START_TIME + 900);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.rating, 5.0);
+    let result = env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 2, 4)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::TripAlreadyRated);
+
+    setup_fare_trip(&mut env, &driver, &passenger, 3, START_TIME + 1_000, 5.0).await;
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.rating, (5.0 + NEUTRAL_RATING) / 2.0);
+
+    // A trip the driver opened alone only names its passenger.
+    env.set_unix_timestamp(START_TIME + 2_000).await;
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 4, START_TIME + 2_000)], &[&driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 2_900).await;
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 4, START_TIME + 2_900, 8, 900, 50_000, 5.0)], &[&driver])
+        .await
+        .unwrap();
+    let result = env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 4, 5)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::PassengerSignatureRequired);
+
+    // Co-signing without escrowing the fare leaves the trip verifiable and scored.
+    env.set_unix_timestamp(START_TIME + 4_800).await;
+    let submit = |signer: &Pubkey| {
+        with_passenger(submit_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 5, START_TIME + 3_000, 12, 1_800, 1_000), signer)
+    };
+    let result = env.send(&[submit(&stranger.pubkey())], &[&driver, &stranger]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    env.send(
+        &[
+            submit(&passenger.pubkey()),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 5, 0, CHUNK_HASH),
+            verify_trip_ix(&payer, &driver.pubkey(), 5, START_TIME + 4_800, 4.5, telemetry_chain(&[CHUNK_HASH])),
+        ],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 5, 4)], &[&passenger])
+        .await
+        .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 5)).await;
+    assert!(trip.passenger_signed);
+    assert!(!trip.has_fare_escrow);
+    assert_eq!(trip.verification_status, 1);
+    assert!(trip.score > 0);
+    assert_eq!(trip.passenger_rating, 4);
+
+    env.set_unix_timestamp(START_TIME + 1_900 + PASSENGER_RATING_WINDOW + 1).await;
+    let result = env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), 3, 1)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::RatingWindowClosed);
+}
+
+#[tokio::test]
+async fn harsh_passenger_ratings_are_down_weighted() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.refresh_blockhash().await;
+    let result = env.send(&[initialize_passenger_ix(&payer, &passenger.pubkey())], &[&passenger]).await;
+    assert!(result.is_err());
+    let account: PassengerAccount = env.fetch(passenger_pda(&passenger.pubkey())).await;
+    assert_eq!(account.passenger_pubkey, passenger.pubkey());
+    assert_eq!(account.reliability, 1.0);
+
+    let mut expected_total = 0.0;
+    for trip_id in 1..=3u64 {
+        setup_fare_trip(&mut env, &driver, &passenger, trip_id, START_TIME + trip_id as i64 * 1_000, 5.0).await;
+        env.send(&[rate_trip_ix(&passenger.pubkey(), &driver.pubkey(), trip_id, 1)], &[&passenger])
+            .await
+            .unwrap();
+        let weight = 2.0 / (trip_id as f32 + 2.0);
+        expected_total += weight + (1.0 - weight) * NEUTRAL_RATING;
+    }
+
+    let account: PassengerAccount = env.fetch(passenger_pda(&passenger.pubkey())).await;
+    assert_eq!(account.ratings_given, 3);
+    assert_eq!(account.low_ratings_given, 3);
+    assert_eq!(account.reliability, 0.4);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.rated_trips, 3);
+    assert!((account.rating - expected_total / 3.0).abs() < 1e-5);
+    assert!(account.rating > 1.0);
+}
+
+#[tokio::test]
+async fn escrowed_fare_is_released_to_the_driver_on_completion() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    let trip_account = trip_pda(&driver.pubkey(), 2);
+    let fare_escrow = fare_escrow_pda(&trip_account);
+
+    let result = env
+        .send(&[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 0)], &[&driver, &passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidFareEscrow);
+    // Without the passenger's profile the fare could never be settled.
+    let stranger = env.funded_keypair(1_000_000_000).await;
+    let result = env
+        .send(&[create_trip_with_fare_ix(&payer, &driver.pubkey(), &stranger.pubkey(), 2, START_TIME, 50_000_000)], &[&driver, &stranger])
+        .await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+    env.send(
+        &[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 50_000_000)],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    let trip: TripAccount = env.fetch(trip_account).await;
+    assert!(trip.has_fare_escrow);
+    assert_eq!(trip.passenger_pubkey, passenger.pubkey());
+    assert_eq!(trip.fare, 50_000_000);
+    let escrow: FareEscrow = env.fetch(fare_escrow).await;
+    assert_eq!(escrow.amount, 50_000_000);
+    assert_eq!(escrow.mint, Pubkey::default());
+    let escrow_lamports = env.lamports(fare_escrow).await;
+    let passenger_before = env.lamports(passenger.pubkey()).await;
+    let driver_before = env.lamports(driver.pubkey()).await;
+
+    env.set_unix_timestamp(START_TIME + 900).await;
+    let complete = |fare| complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, fare, 4.0);
+    let result = env.send(&[complete(50_000_000)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::FareEscrowRequired);
+    let result = env
+        .send(&[with_fare_escrow(complete(60_000_000), &trip_account, &passenger.pubkey(), None)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidFareEscrow);
+    env.send(&[with_fare_escrow(complete(50_000_000), &trip_account, &passenger.pubkey(), None)], &[&driver])
+        .await
+        .unwrap();
+
+    assert_eq!(env.lamports(driver.pubkey()).await, driver_before + 50_000_000);
+    assert_eq!(env.lamports(passenger.pubkey()).await, passenger_before + escrow_lamports - 50_000_000);
+    assert!(!env.account_exists(fare_escrow).await);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_earnings, 50_000_000);
+    let account: PassengerAccount = env.fetch(passenger_pda(&passenger.pubkey())).await;
+    assert_eq!(account.trips_taken, 1);
+}
+
+#[tokio::test]
+async fn escrowed_lamport_fare_must_leave_the_recipient_rent_exempt() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    let trip_account = trip_pda(&driver.pubkey(), 2);
+    env.send(&[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 50_000)], &[&driver, &passenger])
+        .await
+        .unwrap();
+
+    env.set_unix_timestamp(START_TIME + 900).await;
+    let complete = complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 8, 900, 50_000, 4.0);
+    let result = env.send(&[with_fare_escrow(complete, &trip_account, &passenger.pubkey(), None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::RecipientNotRentExempt);
+    let passenger_before = env.lamports(passenger.pubkey()).await;
+    env.send(&[with_fare_escrow(cancel_trip_ix(&driver.pubkey(), &driver.pubkey(), 2), &trip_account, &passenger.pubkey(), None)], &[&driver])
+        .await
+        .unwrap();
+    assert!(env.lamports(passenger.pubkey()).await > passenger_before + 50_000);
+}
+
+#[tokio::test]
+async fn cancelled_trip_refunds_an_escrowed_token_fare() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    let mint = env.create_mint().await;
+    let passenger_ata = env.create_ata(passenger.pubkey(), mint).await;
+    env.mint_to(mint, passenger_ata, 500_000).await;
+    let trip_account = trip_pda(&driver.pubkey(), 2);
+    let fare_vault = associated_token_address(&fare_escrow_pda(&trip_account), &mint);
+
+    env.send(
+        &[create_trip_with_token_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 200_000, &mint, &passenger_ata)],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(passenger_ata).await, 300_000);
+    assert_eq!(env.token_balance(fare_vault).await, 200_000);
+    let escrow: FareEscrow = env.fetch(fare_escrow_pda(&trip_account)).await;
+    assert_eq!(escrow.mint, mint);
+
+    let cancel = |authority: &Pubkey, token_account: &Pubkey| {
+        with_fare_escrow(cancel_trip_ix(authority, &driver.pubkey(), 2), &trip_account, &passenger.pubkey(), Some((&mint, token_account)))
+    };
+    let result = env.send(&[cancel(&passenger.pubkey(), &passenger_ata)], &[&passenger]).await;
+    assert_program_error(result, DriverTripRewardError::FareEscrowLocked);
+    let driver_ata = env.create_ata(driver.pubkey(), mint).await;
+    let result = env.send(&[cancel(&driver.pubkey(), &driver_ata)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidFareEscrow);
+    env.send(&[cancel(&driver.pubkey(), &passenger_ata)], &[&driver]).await.unwrap();
+
+    assert_eq!(env.token_balance(passenger_ata).await, 500_000);
+    assert!(!env.account_exists(fare_vault).await);
+    let trip: TripAccount = env.fetch(trip_account).await;
+    assert_eq!(trip.status, 2);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.active_trip_opened_at, 0);
+    assert_eq!(account.total_earnings, 0);
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 3, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn passenger_reclaims_escrowed_fare_after_timeout() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let payer = env.payer().pubkey();
+    let trip_account = trip_pda(&driver.pubkey(), 2);
+    env.send(
+        &[create_trip_with_fare_ix(&payer, &driver.pubkey(), &passenger.pubkey(), 2, START_TIME, 50_000_000)],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    let passenger_before = env.lamports(passenger.pubkey()).await;
+    let escrow_lamports = env.lamports(fare_escrow_pda(&trip_account)).await;
+
+    env.set_unix_timestamp(START_TIME + DEFAULT_MAX_TRIP_AGE + 1).await;
+    env.send(
+        &[with_fare_escrow(cancel_trip_ix(&passenger.pubkey(), &driver.pubkey(), 2), &trip_account, &passenger.pubkey(), None)],
+        &[&passenger],
+    )
+    .await
+    .unwrap();
+    // The passenger pays the transaction fee out of the refund.
+    assert!(env.lamports(passenger.pubkey()).await > passenger_before + escrow_lamports - 10_000);
+    let account: PassengerAccount = env.fetch(passenger_pda(&passenger.pubkey())).await;
+    assert_eq!(account.disputes_opened, 1);
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + DEFAULT_MAX_TRIP_AGE, 8, 900, 50_000_000, 4.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn multi_stop_trip_is_scored_per_leg() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let stop_passenger = Pubkey::new_unique();
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(&[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 12, 1_800, 200_000_000)], &[&driver])
+        .await
+        .unwrap();
+    let size_before = env.account_size(trip_pda(&driver.pubkey(), 1)).await;
+    env.send(
+        &[
+            add_trip_leg_ix(&payer, &driver.pubkey(), 1, START_TIME, START_TIME + 60, 4, None),
+            add_trip_leg_ix(&payer, &driver.pubkey(), 1, START_TIME + 60, START_TIME + 1_800, 8, Some(stop_passenger)),
+        ],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.account_size(trip_pda(&driver.pubkey(), 1)).await, size_before + 2 * TRIP_LEG_SPACE);
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_700, START_TIME + 1_800, 1, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidLeg);
+
+    env.send(
+        &[
+            commit_telemetry_chunk_ix(&driver.pubkey(), 1, 0, CHUNK_HASH),
+            verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 1.0, telemetry_chain(&[CHUNK_HASH])),
+        ],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    // First leg: 1.0 * 20 + 50, too short for the duration bonus and under the
+    // fare threshold with a third of the fare. Second leg: capped at 100.
+    assert_eq!(
+        trip.legs,
+        vec![
+            TripLeg { start_time: START_TIME, end_time: START_TIME + 60, distance: 4, passenger: Pubkey::default(), score: 70 },
+            TripLeg { start_time: START_TIME + 60, end_time: START_TIME + 1_800, distance: 8, passenger: stop_passenger, score: 100 },
+        ]
+    );
+    // (70 * 4 + 100 * 8) / 12
+    assert_eq!(trip.score, 90);
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, START_TIME + 1_800, 1, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn trip_legs_must_match_completion_and_are_limited() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 2, START_TIME - 1, START_TIME + 60, 1, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidLeg);
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 2, START_TIME, START_TIME + 60, 0, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidLeg);
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 2, START_TIME + 60, START_TIME, 1, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidLeg);
+
+    env.set_unix_timestamp(START_TIME + 900).await;
+    for leg in 0..MAX_TRIP_LEGS as i64 {
+        env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 2, START_TIME + leg * 50, START_TIME + (leg + 1) * 50, 1, None)], &[&driver])
+            .await
+            .unwrap();
+    }
+    let result = env.send(&[add_trip_leg_ix(&payer, &driver.pubkey(), 2, START_TIME + 800, START_TIME + 900, 1, None)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TooManyLegs);
+
+    let result = env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 16, 900, 50_000, 5.0)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::LegsMismatch);
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 16, 800, 50_000, 5.0)], &[&driver])
+        .await
+        .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 2)).await;
+    assert_eq!(trip.legs.len(), MAX_TRIP_LEGS);
+    assert_eq!(trip.status, 1);
+    // Completed legs are scored for their passengers; the trip stays unscored.
+    assert!(trip.legs.iter().all(|leg| leg.score == 100));
+    assert_eq!(trip.score, 0);
+}
+
+#[tokio::test]
+async fn checkpoints_accumulate_and_must_match_completion() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let session_key = env.funded_keypair(1_000_000_000).await;
+    let payer = env.payer().pubkey();
+    env.send(
+        &[
+            create_session_ix(&payer, &driver.pubkey(), session_key.pubkey(), START_TIME + 3_600, SESSION_SCOPE_CHECKPOINT_TRIP),
+            create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME),
+        ],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    let checkpoint = |distance| with_session(checkpoint_trip_ix(&driver.pubkey(), 1, distance), &driver.pubkey(), &session_key.pubkey());
+
+    env.set_unix_timestamp(START_TIME + MIN_CHECKPOINT_INTERVAL - 1).await;
+    let result = env.send(&[checkpoint(0)], &[&session_key]).await;
+    assert_program_error(result, DriverTripRewardError::CheckpointTooSoon);
+    env.set_unix_timestamp(START_TIME + 300).await;
+    // 20 over 300 seconds is 240 an hour, above the default maximum of 200.
+    let result = env.send(&[checkpoint(20)], &[&session_key]).await;
+    assert_program_error(result, DriverTripRewardError::AverageSpeedTooHigh);
+    env.send(&[checkpoint(5)], &[&session_key]).await.unwrap();
+    env.set_unix_timestamp(START_TIME + 900).await;
+    env.send(&[checkpoint_trip_ix(&driver.pubkey(), 1, 3)], &[&driver])
+        .await
+        .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.checkpoints, 2);
+    assert_eq!(trip.checkpoint_distance, 8);
+    assert_eq!(trip.checkpoint_duration, 900);
+    assert_eq!(trip.last_checkpoint_at, START_TIME + 900);
+
+    let result = env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 9, 900, 50_000, 5.0)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::CheckpointMismatch);
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 8, 900, 50_000, 5.0)], &[&driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 1_000).await;
+    let result = env.send(&[checkpoint(1)], &[&session_key]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+
+    // Submitted trips arrive complete and cannot be checkpointed.
+    env.send(&[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME + 1_000, 12, 1_800, 1_000)], &[&driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 2_000).await;
+    let result = env.send(&[checkpoint_trip_ix(&driver.pubkey(), 2, 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn telemetry_chain_anchors_trip_hash() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let chunks = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 12, 1_800, 1_000)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.0, telemetry_chain(&[]))], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TelemetryMissing);
+
+    let result = env.send(&[commit_telemetry_chunk_ix(&driver.pubkey(), 1, 1, chunks[1])], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidChunkIndex);
+
+    // The loop's second commit is identical to the one rejected above.
+    env.refresh_blockhash().await;
+    for (index, chunk) in chunks.iter().enumerate() {
+        env.send(&[commit_telemetry_chunk_ix(&driver.pubkey(), 1, index as u32, *chunk)], &[&driver])
+            .await
+            .unwrap();
+    }
+    env.refresh_blockhash().await;
+    let result = env.send(&[commit_telemetry_chunk_ix(&driver.pubkey(), 1, 1, chunks[1])], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidChunkIndex);
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.telemetry_chunks, 3);
+    assert_eq!(trip.telemetry_chain, telemetry_chain(&chunks));
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.0, telemetry_chain(&chunks[..2]))], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripHash);
+
+    env.send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.0, telemetry_chain(&chunks))], &[&driver])
+        .await
+        .unwrap();
+
+    // The chain is sealed once the trip has been verified.
+    let result = env.send(&[commit_telemetry_chunk_ix(&driver.pubkey(), 1, 3, [4u8; 32])], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+}
+
+#[tokio::test]
+async fn trip_hash_cannot_be_replayed_for_another_trip() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let other = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    setup_verified_trip(&mut env, &driver, 1).await;
+
+    let trip_hash = telemetry_chain(&[CHUNK_HASH]);
+    let record: TripHashRecord = env.fetch(trip_hash_record_pda(&trip_hash)).await;
+    assert_eq!(record.trip_hash, trip_hash);
+    assert_eq!(record.trip_account, trip_pda(&driver.pubkey(), 1));
+    assert_eq!(record.driver_pubkey, driver.pubkey());
+    assert_eq!(record.trip_id, 1);
+    assert_eq!(record.recorded_at, START_TIME + 1_800);
+
+    // Replaying the same telemetry under another driver's trip is rejected.
+    env.send(
+        &[
+            submit_trip_ix(&payer, &other.pubkey(), Pubkey::new_unique(), 1, START_TIME, 12, 1_800, 200_000_000),
+            commit_telemetry_chunk_ix(&other.pubkey(), 1, 0, CHUNK_HASH),
+        ],
+        &[&other],
+    )
+    .await
+    .unwrap();
+    let result = env
+        .send(&[verify_trip_ix(&payer, &other.pubkey(), 1, START_TIME + 1_800, 4.5, trip_hash)], &[&other])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TripHashReplayed);
+
+    let trip: TripAccount = env.fetch(trip_pda(&other.pubkey(), 1)).await;
+    assert_eq!(trip.verification_status, 0);
+    let record: TripHashRecord = env.fetch(trip_hash_record_pda(&trip_hash)).await;
+    assert_eq!(record.trip_account, trip_pda(&driver.pubkey(), 1));
+}
+
+#[tokio::test]
+async fn verify_trip_uses_published_scoring_config() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let weights = ScoringWeights {
+        rating_weight: 10,
+        distance_bonus: 5,
+        duration_bonus: 7,
+        fare_bonus: 0,
+        max_score: 1_000,
+        ..DEFAULT_SCORING_WEIGHTS
+    };
+    let payer = env.payer().pubkey();
+    env.send(&[publish_scoring_config_ix(&payer, &admin.pubkey(), 2, weights)], &[&admin])
+        .await
+        .unwrap();
+
+    let config: ScoringConfig = env.fetch(scoring_config_pda()).await;
+    assert_eq!(config.version, 2);
+    assert_eq!(config.weights, weights);
+
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    // 4.5 * 10 + 5 + 7 + 0
+    assert_eq!(trip.score, 57);
+    assert_eq!(trip.scoring_version, 2);
+
+    // Every version keeps its own weights after newer ones are published.
+    env.send(&[publish_scoring_config_ix(&payer, &admin.pubkey(), 3, DEFAULT_SCORING_WEIGHTS)], &[&admin])
+        .await
+        .unwrap();
+    let version: ScoringVersion = env.fetch(scoring_version_pda(trip.scoring_version)).await;
+    assert_eq!(version.version, 2);
+    assert_eq!(version.weights, weights);
+    let version: ScoringVersion = env.fetch(scoring_version_pda(1)).await;
+    assert_eq!(version.weights, DEFAULT_SCORING_WEIGHTS);
+    let version: ScoringVersion = env.fetch(scoring_version_pda(3)).await;
+    assert_eq!(version.weights, DEFAULT_SCORING_WEIGHTS);
+}
+
+#[tokio::test]
+async fn publish_scoring_config_requires_admin_and_valid_weights() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let impostor = Keypair::new();
+    let payer = env.payer().pubkey();
+
+    let result = env
+        .send(&[publish_scoring_config_ix(&payer, &impostor.pubkey(), 2, DEFAULT_SCORING_WEIGHTS)], &[&impostor])
+        .await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+
+    let weights = ScoringWeights {
+        max_score: 0,
+        ..DEFAULT_SCORING_WEIGHTS
+    };
+    let result = env.send(&[publish_scoring_config_ix(&payer, &admin.pubkey(), 2, weights)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidScoringConfig);
+
+    let weights = ScoringWeights {
+        min_bonus_duration: 4_000,
+        ..DEFAULT_SCORING_WEIGHTS
+    };
+    let result = env.send(&[publish_scoring_config_ix(&payer, &admin.pubkey(), 2, weights)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidScoringConfig);
+
+    let config: ScoringConfig = env.fetch(scoring_config_pda()).await;
+    assert_eq!(config.version, 1);
+}
+
+#[tokio::test]
+async fn trip_rules_are_enforced_on_verify() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(&[update_trip_rules_ix(&admin.pubkey(), 2, 300, 480, 12, 160)], &[&admin])
+        .await
+        .unwrap();
+
+    let rules: TripRules = env.fetch(trip_rules_pda()).await;
+    assert_eq!(rules.min_distance, 2);
+    assert_eq!(rules.min_duration, 480);
+    env.set_unix_timestamp(START_TIME + 7_200).await;
+
+    // (distance, duration, expected error)
+    let cases = [
+        (1, 600, DriverTripRewardError::TripDistanceTooShort),
+        (301, 7_200, DriverTripRewardError::TripDistanceTooLong),
+        (5, 300, DriverTripRewardError::TripDurationTooShort),
+        (2, 3_600, DriverTripRewardError::AverageSpeedTooLow),
+        (50, 600, DriverTripRewardError::AverageSpeedTooHigh),
+    ];
+    for (trip_id, (distance, duration, expected)) in cases.into_iter().enumerate() {
+        let trip_id = trip_id as u64;
+        // A rejected trip stays open, so each case gets its own driver.
+        let driver = setup_driver(&mut env).await;
+        env.send(
+            &[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, distance, duration, 1_000)],
+            &[&driver],
+        )
+        .await
+        .unwrap();
+        let end_time = START_TIME + duration as i64;
+        let result = env
+            .send(&[verify_trip_ix(&payer, &driver.pubkey(), trip_id, end_time, 4.0, TRIP_HASH)], &[&driver])
+            .await;
+        assert_program_error(result, expected);
+    }
+
+    // 12 units over 30 minutes is 24 per hour, inside every bound.
+    env.send(
+        &[
+            submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 10, START_TIME, 12, 1_800, 1_000),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 10, 0, CHUNK_HASH),
+            verify_trip_ix(&payer, &driver.pubkey(), 10, START_TIME + 1_800, 4.0, telemetry_chain(&[CHUNK_HASH])),
+        ],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn trip_rules_are_enforced_on_complete() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(
+        &[
+            update_trip_rules_ix(&admin.pubkey(), 2, 300, 480, 12, 160),
+            create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME),
+        ],
+        &[&admin, &driver],
+    )
+    .await
+    .unwrap();
+    env.set_unix_timestamp(START_TIME + 900).await;
+
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 1, 900, 1_000, 4.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TripDistanceTooShort);
+    let result = env
+        .send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 5, 420, 1_000, 4.0)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TripDurationTooShort);
+
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 5, 900, 1_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn trip_rules_require_admin_and_consistent_bounds() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let impostor = Keypair::new();
+
+    let result = env.send(&[update_trip_rules_ix(&impostor.pubkey(), 2, 300, 480, 12, 160)], &[&impostor]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+
+    let result = env.send(&[update_trip_rules_ix(&admin.pubkey(), 301, 300, 480, 12, 160)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripRules);
+
+    let result = env.send(&[update_trip_rules_ix(&admin.pubkey(), 2, 300, 480, 161, 160)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripRules);
+}
+
+#[tokio::test]
+async fn trip_timestamps_are_checked_against_the_clock() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let other = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let submit = |trip_id, start_time| submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, start_time, 12, 1_800, 1_000);
+
+    let rules: TripRules = env.fetch(trip_rules_pda()).await;
+    assert_eq!(rules.max_clock_drift, DEFAULT_MAX_CLOCK_DRIFT);
+    assert_eq!(rules.max_trip_age, DEFAULT_MAX_TRIP_AGE);
+
+    let result = env.send(&[submit(1, START_TIME + DEFAULT_MAX_CLOCK_DRIFT + 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TimestampInFuture);
+    let result = env.send(&[submit(1, START_TIME - DEFAULT_MAX_TRIP_AGE - 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TimestampTooOld);
+    let result = env
+        .send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME + 3_600)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TimestampInFuture);
+
+    // Within the drift the trip opens, but at the cluster time.
+    env.send(
+        &[
+            submit(1, START_TIME + DEFAULT_MAX_CLOCK_DRIFT),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 1, 0, CHUNK_HASH),
+            create_trip_ix(&payer, &other.pubkey(), Pubkey::new_unique(), 2, START_TIME + DEFAULT_MAX_CLOCK_DRIFT),
+        ],
+        &[&driver, &other],
+    )
+    .await
+    .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&other.pubkey(), 2)).await;
+    assert_eq!(trip.start_time, START_TIME);
+
+    // Verification cannot claim an end time that has not happened yet.
+    env.set_unix_timestamp(START_TIME + 900).await;
+    let verify = verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_860, 4.0, telemetry_chain(&[CHUNK_HASH]));
+    let result = env.send(&[verify], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TimestampInFuture);
+    let complete = complete_trip_ix(&payer, &other.pubkey(), 2, START_TIME + 1_800, 5, 900, 1_000, 4.0);
+    let result = env.send(&[complete], &[&other]).await;
+    assert_program_error(result, DriverTripRewardError::TimestampInFuture);
+
+    let result = env.send(&[set_clock_tolerances_ix(&admin.pubkey(), 60, 0)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripRules);
+    env.send(&[set_clock_tolerances_ix(&admin.pubkey(), 3_600, 60)], &[&admin])
+        .await
+        .unwrap();
+    env.send(&[complete_trip_ix(&payer, &other.pubkey(), 2, START_TIME + 1_800, 5, 900, 1_000, 4.0)], &[&other])
+        .await
+        .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&other.pubkey(), 2)).await;
+    assert_eq!(trip.end_time, START_TIME + 900);
+    let result = env.send(&[submit(3, START_TIME + 900 - 61)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TimestampTooOld);
+}
+
+#[tokio::test]
+async fn driver_has_one_active_trip_and_counted_trips_cannot_overlap() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let submit = |trip_id| submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, 5, 900, 1_000);
+
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.active_trip_id, 1);
+    assert_eq!(account.active_trip_opened_at, START_TIME);
+    let result = env
+        .send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::ActiveTripExists);
+    let result = env.send(&[submit(2)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::ActiveTripExists);
+
+    env.set_unix_timestamp(START_TIME + 900).await;
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 900, 5, 900, 1_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.active_trip_opened_at, 0);
+    assert_eq!(account.last_counted_end_time, START_TIME + 900);
+
+    // A backdated trip over the same 15 minutes can be opened but not counted.
+    env.send(&[submit(2), commit_telemetry_chunk_ix(&driver.pubkey(), 2, 0, CHUNK_HASH)], &[&driver])
+        .await
+        .unwrap();
+    let verify = verify_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 900, 4.0, telemetry_chain(&[CHUNK_HASH]));
+    let result = env.send(&[verify], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TripOverlap);
+
+    // The stuck trip stops blocking once it is older than the maximum trip age.
+    let later = START_TIME + 900 + DEFAULT_MAX_TRIP_AGE + 1;
+    env.set_unix_timestamp(later).await;
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 3, later)], &[&driver])
+        .await
+        .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.active_trip_id, 3);
+}
+
+// -----------------------------------------------------------------
+// Reward pools
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn initialize_reward_pool_creates_vault() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+
+    let reward_pool = reward_pool_pda(pool.pool_id);
+    let account: RewardPoolAccount = env.fetch(reward_pool).await;
+    assert_eq!(account.pool_id, 1);
+    assert_eq!(account.total_rewards, 1_000_000);
+    assert_eq!(account.reward_per_trip, 100_000);
+    assert_eq!(account.distributed_rewards, 0);
+    assert_eq!(account.vault, pool.vault);
+    assert!(account.is_active);
+    assert_eq!(env.token_balance(pool.vault).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn initialize_reward_pool_rejects_invalid_config() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+    let mint = env.create_mint().await;
+
+    let vault = Keypair::new();
+    let ix = initialize_reward_pool_ix(&payer, &admin.pubkey(), &vault.pubkey(), &mint, 1, 1_000, 10, START_TIME, START_TIME);
+    let result = env.send(&[ix], &[&admin, &vault]).await;
+    assert_program_error(result, DriverTripRewardError::RewardPoolNotFound);
+
+    let vault = Keypair::new();
+    let ix = initialize_reward_pool_ix(&payer, &admin.pubkey(), &vault.pubkey(), &mint, 1, 0, 10, START_TIME, START_TIME + 1);
+    let result = env.send(&[ix], &[&admin, &vault]).await;
+    assert_program_error(result, DriverTripRewardError::RewardPoolNotFound);
+}
+
+#[tokio::test]
+async fn initialize_reward_pool_rejects_unsupported_mint_extensions() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+
+    let non_transferable = Keypair::new();
+    let init = initialize_non_transferable_mint(&spl_token_2022::ID, &non_transferable.pubkey()).unwrap();
+    env.create_mint_with_extensions(&non_transferable, &[ExtensionType::NonTransferable], &[init]).await;
+    let permanent_delegate = Keypair::new();
+    let init = initialize_permanent_delegate(&spl_token_2022::ID, &permanent_delegate.pubkey(), &payer).unwrap();
+    env.create_mint_with_extensions(&permanent_delegate, &[ExtensionType::PermanentDelegate], &[init]).await;
+
+    for mint in [non_transferable.pubkey(), permanent_delegate.pubkey()] {
+        let vault = Keypair::new();
+        let ix = initialize_reward_pool_ix(&payer, &admin.pubkey(), &vault.pubkey(), &mint, 1, 1_000, 10, START_TIME, START_TIME + 1);
+        let result = env.send(&[ix], &[&admin, &vault]).await;
+        assert_program_error(result, DriverTripRewardError::UnsupportedMintExtension);
+    }
+    assert!(!env.account_exists(reward_pool_pda(1)).await);
+}
+
+#[tokio::test]
+async fn claim_rewards_records_amount_received_after_transfer_fee() {
+    let mut env = TestEnv::new().await;
+    // 1% fee, capped at 5_000 base units per transfer.
+    let mint = env.create_fee_mint(100, 5_000).await;
+    let pool = setup_pool_with_mint(&mut env, 1, 1_000_000, 100_000, 1_000_000, mint).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let payer = env.payer().pubkey();
+
+    env.send(
+        &[claim_rewards_to_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver.pubkey())],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(env.token_balance(pool.vault).await, 900_000);
+    assert_eq!(env.token_balance(associated_token_address(&driver.pubkey(), &pool.mint)).await, 99_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(pool.pool_id)).await;
+    assert_eq!(account.distributed_rewards, 100_000);
+    assert_eq!(account.total_transfer_fees, 1_000);
+    assert_eq!(account.total_trip_rewards, 99_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 99_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_supports_spl_token_mint() {
+    let mut env = TestEnv::new().await;
+    let mint = env.create_spl_token_mint().await;
+    let pool = setup_pool_with_mint(&mut env, 1, 1_000_000, 100_000, 1_000_000, mint).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let payer = env.payer().pubkey();
+    assert_eq!(env.owner(pool.vault).await, anchor_spl::token::ID);
+
+    // The claim creates the driver's account under the SPL Token program.
+    let driver_ata = get_associated_token_address_with_program_id(&driver.pubkey(), &mint, &anchor_spl::token::ID);
+    let ix = claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &mint, &driver_ata);
+    env.send(&[with_token_program(ix, &anchor_spl::token::ID)], &[&driver])
+        .await
+        .unwrap();
+
+    assert_eq!(env.owner(driver_ata).await, anchor_spl::token::ID);
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+    assert_eq!(env.token_balance(pool.vault).await, 900_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(pool.pool_id)).await;
+    assert_eq!(account.distributed_rewards, 100_000);
+    assert_eq!(account.total_transfer_fees, 0);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 100_000);
+}
+
+#[tokio::test]
+async fn sol_pool_pays_lamports_under_the_same_pool_rules() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+    let reward_pool = reward_pool_pda(1);
+    let sol_vault = sol_vault_pda(&reward_pool);
+    env.send(
+        &[
+            initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 1, 3_000_000_000, 1_000_000_000, START_TIME, START_TIME + 86_400),
+            fund_sol_reward_pool_ix(&payer, 1, 5_000_000_000),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let pool: RewardPoolAccount = env.fetch(reward_pool).await;
+    assert!(pool.is_native);
+    assert_eq!(pool.vault, sol_vault);
+    let vault_before = env.lamports(sol_vault).await;
+
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let destination = Pubkey::new_unique();
+    let admin = env.admin.insecure_clone();
+    env.send(&[set_pool_claim_limits_ix(&admin.pubkey(), 1, 1_000_000_000, 0, 0)], &[&admin])
+        .await
+        .unwrap();
+
+    let claim = || claim_sol_rewards_ix(&payer, &driver.pubkey(), 1, &destination);
+    env.send(&[with_trip_receipt(claim(), &driver.pubkey(), 1)], &[&driver])
+        .await
+        .unwrap();
+    assert_eq!(env.lamports(destination).await, 1_000_000_000);
+    assert_eq!(env.lamports(sol_vault).await, vault_before - 1_000_000_000);
+    let pool: RewardPoolAccount = env.fetch(reward_pool).await;
+    assert_eq!(pool.distributed_rewards, 1_000_000_000);
+    assert_eq!(pool.total_trip_rewards, 1_000_000_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 1_000_000_000);
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.rewarded_at, START_TIME + 1_800);
+
+    env.refresh_blockhash().await;
+    let result = env.send(&[with_trip_receipt(claim(), &driver.pubkey(), 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TripAlreadyRewarded);
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::DailyRewardCapExceeded);
+}
+
+#[tokio::test]
+async fn sol_pool_vault_stays_rent_exempt() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+    let sol_vault = sol_vault_pda(&reward_pool_pda(1));
+    env.send(
+        &[initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 1, 10_000_000_000, 1_000_000_000, START_TIME, START_TIME + 86_400)],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let rent_exempt = env.lamports(sol_vault).await;
+    env.send(&[fund_sol_reward_pool_ix(&payer, 1, 1_500_000_000)], &[]).await.unwrap();
+
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let claim = || claim_sol_rewards_ix(&payer, &driver.pubkey(), 1, &driver.pubkey());
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::SolVaultInsufficient);
+    assert_eq!(env.lamports(sol_vault).await, rent_exempt + 500_000_000);
+}
+
+#[tokio::test]
+async fn sol_pools_check_claim_kind_and_recipient_rent() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let token_pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let admin = Keypair::new();
+    env.send(
+        &[
+            initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 2, 1_000_000, 100_000, START_TIME, START_TIME + 86_400),
+            fund_sol_reward_pool_ix(&payer, 2, 1_000_000),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+
+    let driver_ata = env.create_ata(driver.pubkey(), token_pool.mint).await;
+    let result = env
+        .send(&[claim_rewards_ix(&payer, &driver.pubkey(), 2, &token_pool.vault, &token_pool.mint, &driver_ata)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidRewardPoolKind);
+
+    // 100_000 lamports cannot make an empty wallet rent exempt.
+    let destination = Pubkey::new_unique();
+    let result = env.send(&[claim_sol_rewards_ix(&payer, &driver.pubkey(), 2, &destination)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::RecipientNotRentExempt);
+    assert!(!env.account_exists(destination).await);
+    let wallet = env.funded_keypair(1_000_000_000).await;
+    env.send(&[claim_sol_rewards_ix(&payer, &driver.pubkey(), 2, &wallet.pubkey())], &[&driver])
+        .await
+        .unwrap();
+    assert_eq!(env.lamports(wallet.pubkey()).await, 1_000_100_000);
+}
+
+#[tokio::test]
+async fn protocol_fee_is_bounded_and_split_off_token_claims() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    let treasury = Keypair::new().pubkey();
+    let treasury_ata = env.create_ata(treasury, pool.mint).await;
+
+    let result = env.send(&[set_protocol_fee_ix(&payer, 500, &treasury)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let result = env.send(&[set_protocol_fee_ix(&admin.pubkey(), MAX_PROTOCOL_FEE_BPS + 1, &treasury)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::FeeTooHigh);
+    let result = env.send(&[set_protocol_fee_ix(&admin.pubkey(), 500, &Pubkey::default())], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTreasury);
+    env.send(&[set_protocol_fee_ix(&admin.pubkey(), 500, &treasury)], &[&admin])
+        .await
+        .unwrap();
+
+    let claim = || claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTreasury);
+    let result = env.send(&[with_treasury(claim(), &driver_ata)], &[&driver]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::ConstraintTokenOwner);
+
+    env.send(&[with_treasury(claim(), &treasury_ata)], &[&driver]).await.unwrap();
+    assert_eq!(env.token_balance(pool.vault).await, 900_000);
+    assert_eq!(env.token_balance(treasury_ata).await, 5_000);
+    assert_eq!(env.token_balance(driver_ata).await, 95_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(pool.pool_id)).await;
+    assert_eq!(account.distributed_rewards, 100_000);
+    assert_eq!(account.total_protocol_fees, 5_000);
+    assert_eq!(account.total_trip_rewards, 95_000);
+    let state: DriverPoolState = env.fetch(driver_pool_pda(&reward_pool_pda(pool.pool_id), &driver.pubkey())).await;
+    assert_eq!(state.total_claimed, 100_000);
+    assert_eq!(state.total_protocol_fees, 5_000);
+    assert_eq!(state.total_received, 95_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 95_000);
+}
+
+#[tokio::test]
+async fn protocol_fee_is_split_off_sol_claims() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    env.send(
+        &[
+            initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 1, 3_000_000_000, 1_000_000_000, START_TIME, START_TIME + 86_400),
+            fund_sol_reward_pool_ix(&payer, 1, 2_000_000_000),
+            set_protocol_fee_ix(&admin.pubkey(), MAX_PROTOCOL_FEE_BPS, &treasury),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let destination = Pubkey::new_unique();
+
+    let claim = || claim_sol_rewards_ix(&payer, &driver.pubkey(), 1, &destination);
+    let result = env.send(&[with_treasury(claim(), &Pubkey::new_unique())], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidTreasury);
+    env.send(&[with_treasury(claim(), &treasury)], &[&driver]).await.unwrap();
+
+    assert_eq!(env.lamports(treasury).await, 100_000_000);
+    assert_eq!(env.lamports(destination).await, 900_000_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(1)).await;
+    assert_eq!(account.distributed_rewards, 1_000_000_000);
+    assert_eq!(account.total_protocol_fees, 100_000_000);
+    assert_eq!(account.total_trip_rewards, 900_000_000);
+}
+
+#[tokio::test]
+async fn reward_split_donates_part_of_token_claims() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    let charity = Pubkey::new_unique();
+    let friend = Pubkey::new_unique();
+    let charity_ata = env.create_ata(charity, pool.mint).await;
+    let friend_ata = env.create_ata(friend, pool.mint).await;
+    let share = |wallet: Pubkey, share_bps: u16| SplitShare { wallet, share_bps };
+
+    let invalid_splits = [
+        vec![share(charity, 9_000), share(friend, 1_001)],
+        vec![share(charity, 1_000), share(charity, 500)],
+        vec![share(charity, 0)],
+        (0..=MAX_SPLIT_BENEFICIARIES).map(|_| share(Pubkey::new_unique(), 100)).collect(),
+    ];
+    for shares in invalid_splits {
+        let result = env.send(&[set_reward_split_ix(&payer, &driver.pubkey(), shares)], &[&driver]).await;
+        assert_program_error(result, DriverTripRewardError::InvalidRewardSplit);
+    }
+    env.send(
+        &[set_reward_split_ix(&payer, &driver.pubkey(), vec![share(charity, 1_000), share(friend, 500)])],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    // Claims must honour the split, with beneficiary accounts in split order.
+    let claim = || claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRewardSplit);
+    let result = env
+        .send(&[with_reward_split(claim(), &driver.pubkey(), &[friend_ata, charity_ata])], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidRewardSplit);
+    env.send(&[with_reward_split(claim(), &driver.pubkey(), &[charity_ata, friend_ata])], &[&driver])
+        .await
+        .unwrap();
+
+    assert_eq!(env.token_balance(charity_ata).await, 10_000);
+    assert_eq!(env.token_balance(friend_ata).await, 5_000);
+    assert_eq!(env.token_balance(driver_ata).await, 85_000);
+    let split: RewardSplit = env.fetch(reward_split_pda(&driver.pubkey())).await;
+    assert_eq!(split.total_donated, 15_000);
+    assert_eq!(split.beneficiaries[0].total_donated, 10_000);
+    assert_eq!(split.beneficiaries[1].total_donated, 5_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 85_000);
+
+    // Dropping a beneficiary keeps the running totals of the others.
+    env.send(&[set_reward_split_ix(&payer, &driver.pubkey(), vec![share(charity, 2_000)])], &[&driver])
+        .await
+        .unwrap();
+    let split: RewardSplit = env.fetch(reward_split_pda(&driver.pubkey())).await;
+    assert_eq!(split.beneficiaries.len(), 1);
+    assert_eq!(split.beneficiaries[0].total_donated, 10_000);
+
+    // An empty split stops donating.
+    env.send(&[set_reward_split_ix(&payer, &driver.pubkey(), vec![])], &[&driver])
+        .await
+        .unwrap();
+    env.refresh_blockhash().await;
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    assert_eq!(env.token_balance(driver_ata).await, 185_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert!(!account.has_reward_split);
+}
+
+#[tokio::test]
+async fn reward_split_donates_part_of_sol_claims() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = Keypair::new();
+    env.send(
+        &[
+            initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 1, 3_000_000_000, 1_000_000_000, START_TIME, START_TIME + 86_400),
+            fund_sol_reward_pool_ix(&payer, 1, 2_000_000_000),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let charity = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    env.send(
+        &[set_reward_split_ix(&payer, &driver.pubkey(), vec![SplitShare { wallet: charity, share_bps: 2_000 }])],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    let claim = claim_sol_rewards_ix(&payer, &driver.pubkey(), 1, &destination);
+    env.send(&[with_reward_split(claim, &driver.pubkey(), &[charity])], &[&driver])
+        .await
+        .unwrap();
+
+    assert_eq!(env.lamports(charity).await, 200_000_000);
+    assert_eq!(env.lamports(destination).await, 800_000_000);
+    let split: RewardSplit = env.fetch(reward_split_pda(&driver.pubkey())).await;
+    assert_eq!(split.total_donated, 200_000_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(1)).await;
+    assert_eq!(account.distributed_rewards, 1_000_000_000);
+    assert_eq!(account.total_trip_rewards, 800_000_000);
+    assert_eq!(account.total_transfer_fees, 0);
+}
+
+#[tokio::test]
+async fn claim_rewards_transfers_from_vault() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+
+    env.send(
+        &[claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(env.token_balance(pool.vault).await, 900_000);
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(pool.pool_id)).await;
+    assert_eq!(account.distributed_rewards, 100_000);
+    assert_eq!(account.total_trip_rewards, 100_000);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_rewards, 100_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_creates_destination_token_account() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let payer = env.payer().pubkey();
+    let hardware_wallet = Pubkey::new_unique();
+    let driver_ata = associated_token_address(&driver.pubkey(), &pool.mint);
+    let wallet_ata = associated_token_address(&hardware_wallet, &pool.mint);
+    assert!(!env.account_exists(driver_ata).await);
+
+    env.send(
+        &[claim_rewards_to_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver.pubkey())],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+
+    // The driver can direct later claims to another wallet, whose account is created too.
+    env.send(
+        &[claim_rewards_to_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &hardware_wallet)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(wallet_ata).await, 100_000);
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+
+    // The token account must be the destination owner's associated account.
+    let mut ix = claim_rewards_to_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &hardware_wallet);
+    let destination = ix.accounts.iter().position(|meta| meta.pubkey == wallet_ata).unwrap();
+    ix.accounts[destination].pubkey = driver_ata;
+    let result = env.send(&[ix], &[&driver]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::ConstraintTokenOwner);
+    assert_eq!(env.token_balance(pool.vault).await, 800_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_requires_completed_trip() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+
+    let result = env
+        .send(
+            &[claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata)],
+            &[&driver],
+        )
+        .await;
+    assert_program_error(result, DriverTripRewardError::RewardPoolNotFound);
+    assert_eq!(env.token_balance(pool.vault).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_rejects_another_drivers_trip() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    let other = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &other, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+
+    let claim = claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+    let result = env.send(&[with_trip_receipt(claim, &other.pubkey(), 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotFound);
+    assert_eq!(env.token_balance(pool.vault).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_rejects_exhausted_pool() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 100_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    let claim = || claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InsufficientRewards);
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_rejects_claim_exceeding_pool() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 150_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    let claim = || claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InsufficientRewards);
+    assert_eq!(env.token_balance(pool.vault).await, 900_000);
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+}
+
+#[tokio::test]
+async fn claim_rewards_enforces_per_driver_caps_and_cooldown() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    let claim = || claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata);
+
+    let result = env.send(&[set_pool_claim_limits_ix(&payer, pool.pool_id, 250_000, 300_000, 600)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let result = env.send(&[set_pool_claim_limits_ix(&admin.pubkey(), pool.pool_id, 250_000, 300_000, -1)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidClaimLimits);
+    env.send(&[set_pool_claim_limits_ix(&admin.pubkey(), pool.pool_id, 250_000, 300_000, 600)], &[&admin])
+        .await
+        .unwrap();
+
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::ClaimCooldownActive);
+
+    env.set_unix_timestamp(START_TIME + 2_400).await;
+    env.refresh_blockhash().await;
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.set_unix_timestamp(START_TIME + 3_000).await;
+    env.refresh_blockhash().await;
+    let (result, logs) = env.send_with_logs(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::DailyRewardCapExceeded);
+    assert_program_log(&logs, "Daily reward cap exceeded: 50000 remaining of 250000");
+
+    // A new day resets the daily cap but not the epoch cap.
+    env.set_unix_timestamp(START_TIME + 3_000 + SECONDS_PER_DAY).await;
+    env.refresh_blockhash().await;
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    env.set_unix_timestamp(START_TIME + 3_600 + SECONDS_PER_DAY).await;
+    env.refresh_blockhash().await;
+    let (result, logs) = env.send_with_logs(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::EpochRewardCapExceeded);
+    assert_program_log(&logs, "Epoch reward cap exceeded: 0 remaining of 300000");
+
+    let epoch = env.clock().await.epoch;
+    env.set_epoch(epoch + 1).await;
+    env.refresh_blockhash().await;
+    env.send(&[claim()], &[&driver]).await.unwrap();
+
+    assert_eq!(env.token_balance(driver_ata).await, 400_000);
+    let state: DriverPoolState = env.fetch(driver_pool_pda(&reward_pool_pda(pool.pool_id), &driver.pubkey())).await;
+    assert_eq!(state.reward_pool, reward_pool_pda(pool.pool_id));
+    assert_eq!(state.driver_pubkey, driver.pubkey());
+    assert_eq!(state.epoch, epoch + 1);
+    assert_eq!(state.claimed_this_epoch, 100_000);
+    assert_eq!(state.claimed_today, 200_000);
+    assert_eq!(state.total_claimed, 400_000);
+    assert_eq!(state.last_claim_at, START_TIME + 3_600 + SECONDS_PER_DAY);
+}
+
+#[tokio::test]
+async fn distribute_reward_updates_pool_accounting() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 0).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let payer = env.payer().pubkey();
+
+    env.send(&[distribute_reward_ix(&payer, &driver.pubkey(), pool.pool_id)], &[&driver])
+        .await
+        .unwrap();
+
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(pool.pool_id)).await;
+    assert_eq!(account.distributed_rewards, 100_000);
+    assert_eq!(account.total_trip_rewards, 100_000);
+}
+
+#[tokio::test]
+async fn distribute_reward_rejects_invalid_state() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 150_000, 100_000, 0).await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let distribute = || distribute_reward_ix(&payer, &driver.pubkey(), pool.pool_id);
+
+    let result = env.send(&[distribute()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::RewardPoolNotFound);
+
+    setup_verified_trip(&mut env, &driver, 1).await;
+    env.send(&[distribute()], &[&driver]).await.unwrap();
+    env.refresh_blockhash().await;
+    let result = env.send(&[distribute()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::InsufficientRewards);
+}
+
+// -----------------------------------------------------------------
+// Suspension
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn suspended_driver_is_rejected_everywhere() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_trip(&mut env, &driver, 1).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let payer = env.payer().pubkey();
+    env.send(&[create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME)], &[&driver])
+        .await
+        .unwrap();
+
+    env.send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0, 3, None)], &[&admin])
+        .await
+        .unwrap();
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert!(!account.is_active);
+    assert_eq!(account.suspension_reason, 3);
+    assert_eq!(account.suspension_count, 1);
+
+    let blocked = [
+        submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 3, START_TIME, 10, 600, 1_000),
+        create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 4, START_TIME),
+        complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 600, 8, 600, 1_000, 4.0),
+        claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata),
+        distribute_reward_ix(&payer, &driver.pubkey(), pool.pool_id),
+    ];
+    for ix in blocked {
+        let result = env.send(&[ix], &[&driver]).await;
+        assert_program_error(result, DriverTripRewardError::DriverSuspended);
+    }
+
+    env.send(&[reactivate_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0)], &[&admin])
+        .await
+        .unwrap();
+    env.send(
+        &[claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(driver_ata).await, 100_000);
+}
+
+#[tokio::test]
+async fn suspended_driver_cannot_verify_trip() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    env.send(
+        &[submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 10, 600, 1_000)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    env.send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0, 1, None)], &[&admin])
+        .await
+        .unwrap();
+
+    let result = env
+        .send(&[verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 600, 4.0, TRIP_HASH)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::DriverSuspended);
+}
+
+#[tokio::test]
+async fn suspension_expires_and_history_is_kept() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let now = env.clock().await.unix_timestamp;
+
+    env.send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0, 2, Some(now + 3_600))], &[&admin])
+        .await
+        .unwrap();
+    let submit = |trip_id| submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, 10, 600, 1_000);
+    let result = env.send(&[submit(1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::DriverSuspended);
+
+    env.set_unix_timestamp(now + 3_600).await;
+    env.send(&[submit(1)], &[&driver]).await.unwrap();
+
+    env.send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 1, 5, None)], &[&admin])
+        .await
+        .unwrap();
+    env.send(&[reactivate_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 1)], &[&admin])
+        .await
+        .unwrap();
+
+    let first: SuspensionRecord = env.fetch(suspension_pda(&driver.pubkey(), 0)).await;
+    assert_eq!(first.reason_code, 2);
+    assert_eq!(first.expires_at, now + 3_600);
+    assert_eq!(first.suspended_by, admin.pubkey());
+    assert_eq!(first.reactivated_at, 0);
+    let second: SuspensionRecord = env.fetch(suspension_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(second.index, 1);
+    assert_eq!(second.reason_code, 5);
+    assert_eq!(second.reactivated_by, admin.pubkey());
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert!(account.is_active);
+    assert_eq!(account.suspension_count, 2);
+}
+
+#[tokio::test]
+async fn suspension_requires_admin_and_valid_state() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let now = env.clock().await.unix_timestamp;
+    let impostor = Keypair::new();
+
+    let result = env
+        .send(&[suspend_driver_ix(&payer, &impostor.pubkey(), &driver.pubkey(), 0, 1, None)], &[&impostor])
+        .await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+
+    let result = env
+        .send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0, 1, Some(now - 1))], &[&admin])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidSuspension);
+
+    env.send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0, 1, None)], &[&admin])
+        .await
+        .unwrap();
+    let result = env
+        .send(&[suspend_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 1, 1, None)], &[&admin])
+        .await;
+    assert_program_error(result, DriverTripRewardError::DriverSuspended);
+
+    env.send(&[reactivate_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0)], &[&admin])
+        .await
+        .unwrap();
+    env.refresh_blockhash().await;
+    let result = env
+        .send(&[reactivate_driver_ix(&payer, &admin.pubkey(), &driver.pubkey(), 0)], &[&admin])
+        .await;
+    assert_program_error(result, DriverTripRewardError::DriverNotSuspended);
+}
+
+#[tokio::test]
+async fn unsafe_driving_reports_penalize_scores_and_put_driver_under_review() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.insecure_clone();
+    let payer = env.payer().pubkey();
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let evidence = [9u8; 32];
+
+    let result = env.send(&[initialize_safety_config_ix(&payer, &payer, [30, 20, 10], 2)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let result = env.send(&[initialize_safety_config_ix(&payer, &admin.pubkey(), [101, 20, 10], 2)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidSafetyConfig);
+    env.send(&[initialize_safety_config_ix(&payer, &admin.pubkey(), [30, 20, 10], 2)], &[&admin])
+        .await
+        .unwrap();
+    let result = env.send(&[update_safety_config_ix(&admin.pubkey(), [30, 20, 10], 0)], &[&admin]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidSafetyConfig);
+
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[
+            with_passenger(
+                submit_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 1, START_TIME, 12, 1_800, 200_000_000),
+                &passenger.pubkey(),
+            ),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 1, 0, CHUNK_HASH),
+            verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.5, telemetry_chain(&[CHUNK_HASH])),
+        ],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+
+    let stranger = setup_passenger(&mut env).await;
+    let result = env
+        .send(&[report_unsafe_driving_ix(&stranger.pubkey(), &driver.pubkey(), 1, UNSAFE_DRIVING_SPEEDING, evidence)], &[&stranger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let result = env
+        .send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 1, 3, evidence)], &[&passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidReport);
+    let result = env
+        .send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 1, UNSAFE_DRIVING_SPEEDING, [0u8; 32])], &[&passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidReport);
+    let report = || report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 1, UNSAFE_DRIVING_SPEEDING, evidence);
+    env.send(&[report()], &[&passenger]).await.unwrap();
+
+    let trip_account = trip_pda(&driver.pubkey(), 1);
+    let trip: TripAccount = env.fetch(trip_account).await;
+    assert_eq!(trip.score, 70);
+    let record: UnsafeDrivingReport = env.fetch(unsafe_report_pda(&trip_account)).await;
+    assert_eq!(record.passenger_pubkey, passenger.pubkey());
+    assert_eq!(record.category, UNSAFE_DRIVING_SPEEDING);
+    assert_eq!(record.evidence_hash, evidence);
+    assert_eq!(record.penalty, 30);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_score, 70);
+    assert_eq!(account.unsafe_reports, 1);
+    assert!(!account.under_review);
+    let account: PassengerAccount = env.fetch(passenger_pda(&passenger.pubkey())).await;
+    assert_eq!(account.disputes_opened, 1);
+    env.refresh_blockhash().await;
+    let result = env.send(&[report()], &[&passenger]).await;
+    assert!(result.is_err());
+
+    env.set_unix_timestamp(START_TIME + 2_000).await;
+    env.send(
+        &[with_passenger(create_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 2, START_TIME + 2_000), &passenger.pubkey())],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    let result = env
+        .send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 2, UNSAFE_DRIVING_PHONE_USE, evidence)], &[&passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidTripStatus);
+    env.set_unix_timestamp(START_TIME + 2_900).await;
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 2_900, 8, 900, 50_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+    env.send(
+        &[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 2, UNSAFE_DRIVING_HARSH_BRAKING, evidence)],
+        &[&passenger],
+    )
+    .await
+    .unwrap();
+    // The completed trip was never scored, so its penalty comes off the driver's total.
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 2)).await;
+    assert_eq!(trip.score, 0);
+    let record: UnsafeDrivingReport = env.fetch(unsafe_report_pda(&trip_pda(&driver.pubkey(), 2))).await;
+    assert_eq!(record.penalty, 10);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_score, 60);
+    assert_eq!(account.unsafe_reports, 2);
+    assert!(account.under_review);
+
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let result = env
+        .send(&[claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata)], &[&driver])
+        .await;
+    assert_program_error(result, DriverTripRewardError::DriverUnderReview);
+
+    let result = env.send(&[clear_driver_review_ix(&payer, &driver.pubkey())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    env.send(&[clear_driver_review_ix(&admin.pubkey(), &driver.pubkey())], &[&admin])
+        .await
+        .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert!(!account.under_review);
+    assert_eq!(account.reports_since_review, 0);
+    assert_eq!(account.unsafe_reports, 2);
+    env.send(&[claim_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata)], &[&driver])
+        .await
+        .unwrap();
+
+    // A penalty larger than the total score is owed against the next scored trips.
+    env.send(&[update_safety_config_ix(&admin.pubkey(), [100, 20, 10], 2)], &[&admin])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 3_000).await;
+    env.send(
+        &[with_passenger(create_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 3, START_TIME + 3_000), &passenger.pubkey())],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+    env.set_unix_timestamp(START_TIME + 3_900).await;
+    env.send(&[complete_trip_ix(&payer, &driver.pubkey(), 3, START_TIME + 3_900, 8, 900, 50_000, 4.0)], &[&driver])
+        .await
+        .unwrap();
+    env.send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 3, UNSAFE_DRIVING_SPEEDING, evidence)], &[&passenger])
+        .await
+        .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_score, 0);
+    assert_eq!(account.penalty_owed, 40);
+
+    // Trips the passenger did not co-sign cannot be reported.
+    env.set_unix_timestamp(START_TIME + 5_800).await;
+    let chunk = [8u8; 32];
+    env.send(
+        &[
+            submit_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 4, START_TIME + 4_000, 12, 1_800, 200_000_000),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 4, 0, chunk),
+            verify_trip_ix(&payer, &driver.pubkey(), 4, START_TIME + 5_800, 4.5, telemetry_chain(&[chunk])),
+        ],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_score, 60);
+    assert_eq!(account.penalty_owed, 0);
+    let result = env
+        .send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 4, UNSAFE_DRIVING_PHONE_USE, evidence)], &[&passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::PassengerSignatureRequired);
+}
+
+// -----------------------------------------------------------------
+// Sessions
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn session_key_runs_trip_without_driver_signature() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let session_key = env.funded_keypair(1_000_000_000).await;
+    let payer = env.payer().pubkey();
+    let now = env.clock().await.unix_timestamp;
+
+    env.send(
+        &[create_session_ix(&payer, &driver.pubkey(), session_key.pubkey(), now + 3_600, SESSION_SCOPE_ALL)],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    let session: SessionAccount = env.fetch(session_pda(&driver.pubkey(), &session_key.pubkey())).await;
+    assert_eq!(session.driver_pubkey, driver.pubkey());
+    assert_eq!(session.expires_at, now + 3_600);
+
+    let as_session = |ix| with_session(ix, &driver.pubkey(), &session_key.pubkey());
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[
+            as_session(submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME, 12, 1_800, 1_000)),
+            as_session(commit_telemetry_chunk_ix(&driver.pubkey(), 2, 0, CHUNK_HASH)),
+            as_session(verify_trip_ix(&payer, &driver.pubkey(), 2, START_TIME + 1_800, 4.0, telemetry_chain(&[CHUNK_HASH]))),
+            as_session(create_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME + 1_800)),
+        ],
+        &[&session_key],
+    )
+    .await
+    .unwrap();
+    env.set_unix_timestamp(START_TIME + 2_700).await;
+    env.send(
+        &[as_session(complete_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 2_700, 5, 900, 1_000, 4.0))],
+        &[&session_key],
+    )
+    .await
+    .unwrap();
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.total_trips, 2);
+    assert_eq!(account.completed_trips, 1);
+}
+
+#[tokio::test]
+async fn session_key_is_limited_by_scope_expiry_and_revocation() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let session_key = env.funded_keypair(1_000_000_000).await;
+    let stranger = env.funded_keypair(1_000_000_000).await;
+    let payer = env.payer().pubkey();
+    let now = env.clock().await.unix_timestamp;
+    env.send(
+        &[create_session_ix(
+            &payer,
+            &driver.pubkey(),
+            session_key.pubkey(),
+            now + 3_600,
+            SESSION_SCOPE_SUBMIT_TRIP | SESSION_SCOPE_CREATE_TRIP | SESSION_SCOPE_COMMIT_TELEMETRY,
+        )],
+        &[&driver],
+    )
+    .await
+    .unwrap();
+    let submit = |trip_id| submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, 12, 1_800, 1_000);
+
+    env.send(&[with_session(submit(1), &driver.pubkey(), &session_key.pubkey())], &[&session_key])
+        .await
+        .unwrap();
+
+    // An unknown signer without a session account.
+    let mut ix = submit(2);
+    ix.accounts.iter_mut().filter(|meta| meta.is_signer && meta.pubkey == driver.pubkey()).for_each(|meta| {
+        meta.pubkey = stranger.pubkey();
+    });
+    let result = env.send(&[ix], &[&stranger]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidSessionAuthority);
+
+    let verify = verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.0, TRIP_HASH);
+    let result = env.send(&[with_session(verify, &driver.pubkey(), &session_key.pubkey())], &[&session_key]).await;
+    assert_program_error(result, DriverTripRewardError::SessionScopeNotAllowed);
+
+    env.set_unix_timestamp(now + 3_600).await;
+    let result = env.send(&[with_session(submit(3), &driver.pubkey(), &session_key.pubkey())], &[&session_key]).await;
+    assert_program_error(result, DriverTripRewardError::SessionExpired);
+
+    env.send(&[revoke_session_ix(&driver.pubkey(), &session_key.pubkey())], &[&driver])
+        .await
+        .unwrap();
+    assert!(!env.account_exists(session_pda(&driver.pubkey(), &session_key.pubkey())).await);
+    let result = env.send(&[with_session(submit(4), &driver.pubkey(), &session_key.pubkey())], &[&session_key]).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn create_session_rejects_invalid_parameters() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let payer = env.payer().pubkey();
+    let now = env.clock().await.unix_timestamp;
+
+    let cases = [
+        (Pubkey::new_unique(), now, SESSION_SCOPE_ALL),
+        (Pubkey::new_unique(), now + driver_trip_reward::MAX_SESSION_DURATION + 1, SESSION_SCOPE_ALL),
+        (Pubkey::new_unique(), now + 60, 0),
+        (Pubkey::new_unique(), now + 60, SESSION_SCOPE_ALL + 1),
+        (driver.pubkey(), now + 60, SESSION_SCOPE_ALL),
+    ];
+    for (session_key, expires_at, scope) in cases {
+        let result = env
+            .send(&[create_session_ix(&payer, &driver.pubkey(), session_key, expires_at, scope)], &[&driver])
+            .await;
+        assert_program_error(result, DriverTripRewardError::InvalidSession);
+    }
+}
+
+// -----------------------------------------------------------------
+// Sponsored rent
+// -----------------------------------------------------------------
+async fn setup_sponsor_treasury(env: &mut TestEnv, relayer: &Pubkey, per_driver_budget: u64, funded: u64) {
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    env.send(
+        &[
+            initialize_sponsor_treasury_ix(&payer, &admin.pubkey(), per_driver_budget, relayer),
+            fund_sponsor_treasury_ix(&payer, funded),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn sponsor_treasury_reimburses_driver_and_trip_rent() {
+    let mut env = TestEnv::new().await;
+    let relayer = env.funded_keypair(1_000_000_000).await;
+    setup_sponsor_treasury(&mut env, &relayer.pubkey(), 1_000_000_000, 1_000_000_000).await;
+    let driver = Keypair::new();
+    let treasury_before = env.lamports(sponsor_treasury_pda()).await;
+
+    env.send(
+        &[
+            with_sponsor(initialize_driver_ix(&relayer.pubkey(), &driver.pubkey())),
+            with_sponsor(submit_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 12, 1_800, 1_000)),
+        ],
+        &[&relayer, &driver],
+    )
+    .await
+    .unwrap();
+
+    let rent = env.lamports(driver_pda(&driver.pubkey())).await + env.lamports(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(env.lamports(relayer.pubkey()).await, 1_000_000_000);
+    assert_eq!(env.lamports(sponsor_treasury_pda()).await, treasury_before - rent);
+
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.sponsored_rent, rent);
+    assert_eq!(account.rent_payer, sponsor_treasury_pda());
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.rent_payer, sponsor_treasury_pda());
+    let treasury: SponsorTreasury = env.fetch(sponsor_treasury_pda()).await;
+    assert_eq!(treasury.total_sponsored, rent);
+
+    // The treasury only reimburses its relayer.
+    let stranger = env.funded_keypair(1_000_000_000).await;
+    let unsponsored = Keypair::new();
+    let result = env
+        .send(&[with_sponsor(initialize_driver_ix(&stranger.pubkey(), &unsponsored.pubkey()))], &[&stranger, &unsponsored])
+        .await;
+    assert_program_error(result, DriverTripRewardError::SponsorRelayerRequired);
+
+    // Unsponsored inits are still paid by the fee payer.
+    let other = setup_driver(&mut env).await;
+    env.send(
+        &[create_trip_ix(&relayer.pubkey(), &other.pubkey(), Pubkey::new_unique(), 2, START_TIME)],
+        &[&relayer, &other],
+    )
+    .await
+    .unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&other.pubkey(), 2)).await;
+    assert_eq!(trip.rent_payer, relayer.pubkey());
+    let account: DriverAccount = env.fetch(driver_pda(&other.pubkey())).await;
+    assert_eq!(account.sponsored_rent, 0);
+}
+
+#[tokio::test]
+async fn sponsor_treasury_enforces_budget_balance_and_admin() {
+    let mut env = TestEnv::new().await;
+    let driver = Keypair::new();
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    setup_sponsor_treasury(&mut env, &payer, 0, 10_000_000).await;
+
+    let result = env.send(&[with_sponsor(initialize_driver_ix(&payer, &driver.pubkey()))], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::SponsorBudgetExceeded);
+
+    env.send(&[update_sponsor_treasury_ix(&admin.pubkey(), 1_000_000_000, &payer, true)], &[&admin])
+        .await
+        .unwrap();
+    env.refresh_blockhash().await;
+    env.send(&[with_sponsor(initialize_driver_ix(&payer, &driver.pubkey()))], &[&driver])
+        .await
+        .unwrap();
+
+    // A budget covering only the driver account.
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    env.send(&[update_sponsor_treasury_ix(&admin.pubkey(), account.sponsored_rent, &payer, true)], &[&admin])
+        .await
+        .unwrap();
+    let submit = |driver: &Keypair, trip_id| {
+        with_sponsor(submit_trip_ix(&payer, &driver.pubkey(), Pubkey::new_unique(), trip_id, START_TIME, 12, 1_800, 1_000))
+    };
+    let result = env.send(&[submit(&driver, 1)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::SponsorBudgetExceeded);
+
+    env.send(&[update_sponsor_treasury_ix(&admin.pubkey(), 1_000_000_000, &payer, false)], &[&admin])
+        .await
+        .unwrap();
+    let result = env.send(&[submit(&driver, 2)], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::SponsorTreasuryInactive);
+
+    let stranger = Keypair::new();
+    let result = env.send(&[update_sponsor_treasury_ix(&stranger.pubkey(), 1_000_000_000, &payer, true)], &[&stranger]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+
+    // Reactivated, the treasury eventually runs out of lamports for new trips.
+    env.send(&[update_sponsor_treasury_ix(&admin.pubkey(), u64::MAX, &payer, true)], &[&admin])
+        .await
+        .unwrap();
+    let result = loop {
+        let driver = setup_driver(&mut env).await;
+        let result = env.send(&[submit(&driver, 1)], &[&driver]).await;
+        if result.is_err() {
+            break result;
+        }
+    };
+    assert_program_error(result, DriverTripRewardError::SponsorTreasuryInsufficient);
+}
+
+#[tokio::test]
+async fn sponsored_trip_legs_are_charged_to_the_sponsor() {
+    let mut env = TestEnv::new().await;
+    let relayer = env.funded_keypair(1_000_000_000).await;
+    setup_sponsor_treasury(&mut env, &relayer.pubkey(), 1_000_000_000, 1_000_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    let treasury_before = env.lamports(sponsor_treasury_pda()).await;
+    env.send(
+        &[with_sponsor(create_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME))],
+        &[&relayer, &driver],
+    )
+    .await
+    .unwrap();
+
+    // Leg growth is refunded with the trip's rent, so only the trip's rent payer covers it.
+    env.set_unix_timestamp(START_TIME + 900).await;
+    let add_leg = || add_trip_leg_ix(&relayer.pubkey(), &driver.pubkey(), 1, START_TIME, START_TIME + 900, 8, None);
+    let result = env.send(&[add_leg()], &[&relayer, &driver]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRentPayer);
+    env.send(&[with_sponsor(add_leg())], &[&relayer, &driver])
+        .await
+        .unwrap();
+
+    let rent = env.lamports(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(env.lamports(relayer.pubkey()).await, 1_000_000_000);
+    assert_eq!(env.lamports(sponsor_treasury_pda()).await, treasury_before - rent);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.sponsored_rent, rent);
+
+    env.send(&[complete_trip_ix(&relayer.pubkey(), &driver.pubkey(), 1, START_TIME + 900, 8, 900, 50_000, 4.0)], &[&relayer, &driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 900 + TRIP_DISPUTE_WINDOW).await;
+    env.send(&[close_trip_ix(&driver.pubkey(), 1, &sponsor_treasury_pda())], &[])
+        .await
+        .unwrap();
+    assert_eq!(env.lamports(sponsor_treasury_pda()).await, treasury_before);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.sponsored_rent, 0);
+}
+
+// -----------------------------------------------------------------
+// Closing trips
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn close_trip_after_dispute_window_folds_history_and_refunds_payer() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let relayer = env.funded_keypair(1_000_000_000).await;
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[
+            submit_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME, 12, 1_800, 1_000),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 1, 0, CHUNK_HASH),
+            verify_trip_ix(&relayer.pubkey(), &driver.pubkey(), 1, START_TIME + 1_800, 4.5, telemetry_chain(&[CHUNK_HASH])),
+            create_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 2, START_TIME),
+        ],
+        &[&relayer, &driver],
+    )
+    .await
+    .unwrap();
+
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotSettled);
+
+    env.set_unix_timestamp(START_TIME + 1_800 + TRIP_DISPUTE_WINDOW).await;
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 2, &relayer.pubkey())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotSettled);
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &Pubkey::new_unique())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRentPayer);
+
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    let rent = env.lamports(trip_pda(&driver.pubkey(), 1)).await;
+    let relayer_before = env.lamports(relayer.pubkey()).await;
+    // Identical to the close rejected inside the dispute window.
+    env.refresh_blockhash().await;
+    env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[])
+        .await
+        .unwrap();
+
+    assert!(!env.account_exists(trip_pda(&driver.pubkey(), 1)).await);
+    assert_eq!(env.lamports(relayer.pubkey()).await, relayer_before + rent);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.closed_trips, 1);
+    assert_eq!(account.trip_history, DriverAccount::next_trip_history(&[0u8; 32], &trip));
+}
+
+#[tokio::test]
+async fn completed_trip_closes_after_dispute_window() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let relayer = env.funded_keypair(1_000_000_000).await;
+    env.send(&[create_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME)], &[&relayer, &driver])
+        .await
+        .unwrap();
+    env.set_unix_timestamp(START_TIME + 900).await;
+    env.send(&[complete_trip_ix(&relayer.pubkey(), &driver.pubkey(), 1, START_TIME + 900, 8, 900, 50_000, 4.0)], &[&relayer, &driver])
+        .await
+        .unwrap();
+
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotSettled);
+    env.set_unix_timestamp(START_TIME + 900 + TRIP_DISPUTE_WINDOW).await;
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.verification_status, 0);
+    let rent = env.lamports(trip_pda(&driver.pubkey(), 1)).await;
+    let relayer_before = env.lamports(relayer.pubkey()).await;
+    // Identical to the close rejected inside the dispute window.
+    env.refresh_blockhash().await;
+    env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[])
+        .await
+        .unwrap();
+
+    assert!(!env.account_exists(trip_pda(&driver.pubkey(), 1)).await);
+    assert_eq!(env.lamports(relayer.pubkey()).await, relayer_before + rent);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.closed_trips, 1);
+    assert_eq!(account.trip_history, DriverAccount::next_trip_history(&[0u8; 32], &trip));
+}
+
+#[tokio::test]
+async fn cancelled_trip_closes_back_to_its_rent_payer() {
+    let mut env = TestEnv::new().await;
+    let driver = setup_driver(&mut env).await;
+    let relayer = env.funded_keypair(1_000_000_000).await;
+    env.set_unix_timestamp(START_TIME + 600).await;
+    env.send(&[create_trip_ix(&relayer.pubkey(), &driver.pubkey(), Pubkey::new_unique(), 1, START_TIME)], &[&relayer, &driver])
+        .await
+        .unwrap();
+
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotSettled);
+    env.send(&[cancel_trip_ix(&driver.pubkey(), &driver.pubkey(), 1)], &[&driver])
+        .await
+        .unwrap();
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &Pubkey::new_unique())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRentPayer);
+
+    let rent = env.lamports(trip_pda(&driver.pubkey(), 1)).await;
+    let relayer_before = env.lamports(relayer.pubkey()).await;
+    // Identical to the close rejected before the trip was cancelled.
+    env.refresh_blockhash().await;
+    env.send(&[close_trip_ix(&driver.pubkey(), 1, &relayer.pubkey())], &[])
+        .await
+        .unwrap();
+
+    assert!(!env.account_exists(trip_pda(&driver.pubkey(), 1)).await);
+    assert_eq!(env.lamports(relayer.pubkey()).await, relayer_before + rent);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.closed_trips, 0);
+    assert_eq!(account.trip_history, [0u8; 32]);
+}
+
+#[tokio::test]
+async fn rewarded_trip_closes_after_dispute_window_and_refunds_sponsor() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    env.send(&[initialize_safety_config_ix(&payer, &admin.pubkey(), [30, 20, 10], 2)], &[&admin])
+        .await
+        .unwrap();
+    setup_sponsor_treasury(&mut env, &payer, 1_000_000_000, 1_000_000_000).await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    let passenger = setup_passenger(&mut env).await;
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let treasury_before = env.lamports(sponsor_treasury_pda()).await;
+    env.set_unix_timestamp(START_TIME + 1_800).await;
+    env.send(
+        &[
+            with_passenger(
+                with_sponsor(submit_trip_ix(&payer, &driver.pubkey(), passenger.pubkey(), 1, START_TIME, 12, 1_800, 1_000)),
+                &passenger.pubkey(),
+            ),
+            commit_telemetry_chunk_ix(&driver.pubkey(), 1, 0, CHUNK_HASH),
+            verify_trip_ix(&payer, &driver.pubkey(), 1, START_TIME + 1_800, 4.5, telemetry_chain(&[CHUNK_HASH])),
+        ],
+        &[&driver, &passenger],
+    )
+    .await
+    .unwrap();
+
+    let claim = || claim_trip_rewards_ix(&payer, &driver.pubkey(), pool.pool_id, &pool.vault, &pool.mint, &driver_ata, 1);
+    env.send(&[claim()], &[&driver]).await.unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.rewarded_at, START_TIME + 1_800);
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim()], &[&driver]).await;
+    assert_program_error(result, DriverTripRewardError::TripAlreadyRewarded);
+
+    // Being rewarded does not cut short the passenger's window to report the trip.
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &sponsor_treasury_pda())], &[]).await;
+    assert_program_error(result, DriverTripRewardError::TripNotSettled);
+    env.set_unix_timestamp(START_TIME + 1_800 + TRIP_DISPUTE_WINDOW + 1).await;
+    let result = env
+        .send(&[report_unsafe_driving_ix(&passenger.pubkey(), &driver.pubkey(), 1, UNSAFE_DRIVING_SPEEDING, [9u8; 32])], &[&passenger])
+        .await;
+    assert_program_error(result, DriverTripRewardError::ReportWindowClosed);
+
+    let result = env.send(&[close_trip_ix(&driver.pubkey(), 1, &payer)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRentPayer);
+    // The treasury passed as a plain rent payer would skip the budget refund.
+    let mut close = close_trip_ix(&driver.pubkey(), 1, &payer);
+    close.accounts.iter_mut().filter(|meta| meta.pubkey == payer).for_each(|meta| meta.pubkey = sponsor_treasury_pda());
+    let result = env.send(&[close], &[]).await;
+    assert_program_error(result, DriverTripRewardError::InvalidRentPayer);
+    // Identical to the close rejected inside the dispute window.
+    env.refresh_blockhash().await;
+    env.send(&[close_trip_ix(&driver.pubkey(), 1, &sponsor_treasury_pda())], &[])
+        .await
+        .unwrap();
+
+    assert!(!env.account_exists(trip_pda(&driver.pubkey(), 1)).await);
+    assert_eq!(env.lamports(sponsor_treasury_pda()).await, treasury_before);
+    let account: DriverAccount = env.fetch(driver_pda(&driver.pubkey())).await;
+    assert_eq!(account.sponsored_rent, 0);
+    let treasury: SponsorTreasury = env.fetch(sponsor_treasury_pda()).await;
+    assert_eq!(treasury.total_refunded, treasury.total_sponsored);
+}
+
+// -----------------------------------------------------------------
+// Validators
+// -----------------------------------------------------------------
+#[tokio::test]
+async fn validator_lifecycle() {
+    let mut env = TestEnv::new().await;
+    let validator = Keypair::new();
+    let payer = env.payer().pubkey();
+
+    env.send(
+        &[
+            initialize_validator_ix(&payer, &validator.pubkey(), "validator-public-key", "validator-private-key"),
+            process_private_data_ix(&payer, &validator.pubkey(), "telemetry", "hash"),
+            update_validator_weights_ix(&payer, &validator.pubkey(), vec![1, 2, 3]),
+        ],
+        &[&validator],
+    )
+    .await
+    .unwrap();
+
+    let account: ValidatorAccount = env.fetch(validator_pda(&validator.pubkey())).await;
+    assert_eq!(account.validator_pubkey, validator.pubkey());
+    assert_eq!(account.public_key, "validator-public-key");
+    assert_eq!(account.total_validations, 2);
+    assert_eq!(account.validator_weight, 1.0);
+    assert!(!account.is_active);
+
+    let result = env.send(&[set_validator_active_ix(&payer, &validator.pubkey(), true)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    let admin = env.admin.insecure_clone();
+    env.send(&[set_validator_active_ix(&admin.pubkey(), &validator.pubkey(), true)], &[&admin])
+        .await
+        .unwrap();
+    let account: ValidatorAccount = env.fetch(validator_pda(&validator.pubkey())).await;
+    assert!(account.is_active);
+    env.send(&[set_validator_active_ix(&admin.pubkey(), &validator.pubkey(), false)], &[&admin])
+        .await
+        .unwrap();
+    let account: ValidatorAccount = env.fetch(validator_pda(&validator.pubkey())).await;
+    assert!(!account.is_active);
+}
+
+#[tokio::test]
+async fn initialize_validator_rejects_short_keys() {
+    let mut env = TestEnv::new().await;
+    let validator = Keypair::new();
+    let payer = env.payer().pubkey();
+
+    let result = env
+        .send(&[initialize_validator_ix(&payer, &validator.pubkey(), "short", "validator-private-key")], &[&validator])
+        .await;
+    assert_program_error(result, DriverTripRewardError::ValidatorAlreadyExists);
+
+    let result = env
+        .send(&[initialize_validator_ix(&payer, &validator.pubkey(), "validator-public-key", "short")], &[&validator])
+        .await;
+    assert_program_error(result, DriverTripRewardError::ValidatorAlreadyExists);
+}
+
+#[tokio::test]
+async fn validator_operations_reject_invalid_data() {
+    let mut env = TestEnv::new().await;
+    let validator = Keypair::new();
+    let payer = env.payer().pubkey();
+    env.send(
+        &[initialize_validator_ix(&payer, &validator.pubkey(), "validator-public-key", "validator-private-key")],
+        &[&validator],
+    )
+    .await
+    .unwrap();
+
+    let result = env
+        .send(&[process_private_data_ix(&payer, &validator.pubkey(), "telemetry", "sign")], &[&validator])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidDataOperation);
+
+    let result = env
+        .send(&[update_validator_weights_ix(&payer, &validator.pubkey(), vec![])], &[&validator])
+        .await;
+    assert_program_error(result, DriverTripRewardError::InvalidDataOperation);
+}
+
+#[tokio::test]
+async fn validator_fees_accrue_on_finalize_and_are_paid_from_the_vault() {
+    let mut env = TestEnv::new().await;
+    let pool = setup_pool(&mut env, 1, 1_000_000, 100_000, 1_000_000).await;
+    let other_pool = setup_pool(&mut env, 2, 1_000_000, 100_000, 1_000_000).await;
+    let driver = setup_driver(&mut env).await;
+    setup_verified_pool_trip(&mut env, &driver, 1, Some(pool.pool_id)).await;
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.reward_pool, reward_pool_pda(pool.pool_id));
+    let validator = Keypair::new();
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    env.send(
+        &[initialize_validator_ix(&payer, &validator.pubkey(), "validator-public-key", "validator-private-key")],
+        &[&validator],
+    )
+    .await
+    .unwrap();
+
+    let result = env.send(&[set_validator_fee_ix(&payer, pool.pool_id, 2_000)], &[]).await;
+    assert_program_error(result, DriverTripRewardError::Unauthorized);
+    env.send(
+        &[
+            set_validator_fee_ix(&admin.pubkey(), pool.pool_id, 2_000),
+            set_validator_fee_ix(&admin.pubkey(), other_pool.pool_id, 2_000),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let finalize = || finalize_trip_ix(&payer, &validator.pubkey(), pool.pool_id, &driver.pubkey(), 1);
+    let result = env.send(&[finalize()], &[&validator]).await;
+    assert_program_error(result, DriverTripRewardError::ValidatorInactive);
+    env.send(&[set_validator_active_ix(&admin.pubkey(), &validator.pubkey(), true)], &[&admin])
+        .await
+        .unwrap();
+    let result = env
+        .send(&[finalize_trip_ix(&payer, &validator.pubkey(), other_pool.pool_id, &driver.pubkey(), 1)], &[&validator])
+        .await;
+    assert_program_error(result, DriverTripRewardError::TripPoolMismatch);
+    // The trip is not a receipt for another pool's claims either.
+    let other_ata = env.create_ata(driver.pubkey(), other_pool.mint).await;
+    let result = env
+        .send(
+            &[claim_trip_rewards_ix(&payer, &driver.pubkey(), other_pool.pool_id, &other_pool.vault, &other_pool.mint, &other_ata, 1)],
+            &[&driver],
+        )
+        .await;
+    assert_program_error(result, DriverTripRewardError::TripPoolMismatch);
+
+    env.refresh_blockhash().await;
+    env.send(&[finalize()], &[&validator]).await.unwrap();
+    let trip: TripAccount = env.fetch(trip_pda(&driver.pubkey(), 1)).await;
+    assert_eq!(trip.validator_pubkey, validator.pubkey());
+    let account: ValidatorAccount = env.fetch(validator_pda(&validator.pubkey())).await;
+    assert_eq!(account.total_validations, 1);
+    let reward_pool = reward_pool_pda(pool.pool_id);
+    let balance: ValidatorFeeBalance = env.fetch(validator_fees_pda(&reward_pool, &validator.pubkey())).await;
+    assert_eq!(balance.claimable, 2_000);
+    assert_eq!(balance.total_earned, 2_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool).await;
+    assert_eq!(account.distributed_rewards, 2_000);
+    assert_eq!(account.total_validator_fees, 2_000);
+
+    env.refresh_blockhash().await;
+    let result = env.send(&[finalize()], &[&validator]).await;
+    assert_program_error(result, DriverTripRewardError::TripAlreadyFinalized);
+
+    let driver_ata = env.create_ata(driver.pubkey(), pool.mint).await;
+    let validator_ata = env.create_ata(validator.pubkey(), pool.mint).await;
+    let claim = |destination: &Pubkey| claim_validator_fees_ix(&validator.pubkey(), pool.pool_id, &pool.vault, &pool.mint, destination);
+    let result = env.send(&[claim(&driver_ata)], &[&validator]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::ConstraintRaw);
+    env.send(&[claim(&validator_ata)], &[&validator]).await.unwrap();
+    assert_eq!(env.token_balance(validator_ata).await, 2_000);
+    assert_eq!(env.token_balance(pool.vault).await, 998_000);
+    let balance: ValidatorFeeBalance = env.fetch(validator_fees_pda(&reward_pool, &validator.pubkey())).await;
+    assert_eq!(balance.claimable, 0);
+    assert_eq!(balance.total_claimed, 2_000);
+
+    env.refresh_blockhash().await;
+    let result = env.send(&[claim(&validator_ata)], &[&validator]).await;
+    assert_program_error(result, DriverTripRewardError::NoValidatorFees);
+}
+
+#[tokio::test]
+async fn validator_fees_are_paid_from_sol_vaults() {
+    let mut env = TestEnv::new().await;
+    let payer = env.payer().pubkey();
+    let admin = env.admin.insecure_clone();
+    let validator = Keypair::new();
+    env.send(
+        &[
+            initialize_sol_reward_pool_ix(&payer, &admin.pubkey(), 1, 3_000_000_000, 1_000_000_000, START_TIME, START_TIME + 86_400),
+            fund_sol_reward_pool_ix(&payer, 1, 2_000_000_000),
+            set_validator_fee_ix(&admin.pubkey(), 1, 10_000_000),
+            initialize_validator_ix(&payer, &validator.pubkey(), "validator-public-key", "validator-private-key"),
+            set_validator_active_ix(&admin.pubkey(), &validator.pubkey(), true),
+        ],
+        &[&admin, &validator],
+    )
+    .await
+    .unwrap();
+    let driver = setup_driver(&mut env).await;
+    setup_verified_pool_trip(&mut env, &driver, 1, Some(1)).await;
+    let sol_vault = sol_vault_pda(&reward_pool_pda(1));
+    let vault_before = env.lamports(sol_vault).await;
+
+    env.send(&[finalize_trip_ix(&payer, &validator.pubkey(), 1, &driver.pubkey(), 1)], &[&validator])
+        .await
+        .unwrap();
+    let validator_before = env.lamports(validator.pubkey()).await;
+    env.send(&[claim_sol_validator_fees_ix(&validator.pubkey(), 1)], &[&validator])
+        .await
+        .unwrap();
+    assert_eq!(env.lamports(validator.pubkey()).await, validator_before + 10_000_000);
+    assert_eq!(env.lamports(sol_vault).await, vault_before - 10_000_000);
+    let account: RewardPoolAccount = env.fetch(reward_pool_pda(1)).await;
+    assert_eq!(account.distributed_rewards, 10_000_000);
+    assert_eq!(account.total_validator_fees, 10_000_000);
+}